@@ -0,0 +1,214 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{cli::Options, game::GameEvent, player::PlayerId, Data};
+
+/// Everything a player has done over their career. Stats that weren't kept yet when a league
+/// was saved start at 0
+#[derive(Clone, Copy, Default, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CareerStats {
+    pub matches: usize,
+    pub serves: usize,
+    pub aces: usize,
+    pub faults: usize,
+    pub double_faults: usize,
+    pub hits: usize,
+    pub misses: usize,
+    pub points_won: usize,
+    pub points_lost: usize,
+    /// Times they traded teams with the player across the court
+    #[serde(alias = "feedbacks")]
+    pub swaps: usize,
+    pub injuries: usize,
+    /// Times weather watched them, which does nothing at all
+    pub watched: usize,
+    /// Whether weather took them out of the league for good
+    #[serde(alias = "defragged")]
+    pub replaced: bool,
+}
+
+impl CareerStats {
+    const fn merge(&mut self, other: &Self) {
+        self.matches += other.matches;
+        self.serves += other.serves;
+        self.aces += other.aces;
+        self.faults += other.faults;
+        self.double_faults += other.double_faults;
+        self.hits += other.hits;
+        self.misses += other.misses;
+        self.points_won += other.points_won;
+        self.points_lost += other.points_lost;
+        self.swaps += other.swaps;
+        self.injuries += other.injuries;
+        self.watched += other.watched;
+        self.replaced |= other.replaced;
+    }
+}
+
+/// Career stats for every player that has taken part in a match
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Ledger {
+    players: BTreeMap<PlayerId, CareerStats>,
+}
+
+impl Ledger {
+    pub const fn new() -> Self {
+        Self {
+            players: BTreeMap::new(),
+        }
+    }
+
+    pub fn get(&self, id: &PlayerId) -> Option<&CareerStats> {
+        self.players.get(id)
+    }
+
+    fn entry(&mut self, id: PlayerId) -> &mut CareerStats {
+        self.players.entry(id).or_default()
+    }
+
+    pub fn record_event(&mut self, event: &GameEvent) {
+        match *event {
+            GameEvent::Serve { player } => self.entry(player).serves += 1,
+            GameEvent::Fault { player } => self.entry(player).faults += 1,
+            // The first fault was already counted on its own
+            GameEvent::DoubleFault { player } => {
+                let stats = self.entry(player);
+                stats.faults += 1;
+                stats.double_faults += 1;
+                stats.points_lost += 1;
+            }
+            GameEvent::Ace { player, receiver } => {
+                self.entry(player).aces += 1;
+                self.entry(receiver).points_lost += 1;
+            }
+            GameEvent::Hit { player }
+            | GameEvent::Lob { player }
+            | GameEvent::DropShot { player }
+            | GameEvent::PowerShot { player } => {
+                self.entry(player).hits += 1;
+            }
+            GameEvent::Miss { player } => {
+                let stats = self.entry(player);
+                stats.misses += 1;
+                stats.points_lost += 1;
+            }
+            // Every other point ends with someone missing the ball or being kept from hitting it
+            GameEvent::Prevented { player } => self.entry(player).points_lost += 1,
+            GameEvent::Score { player } => self.entry(player).points_won += 1,
+            GameEvent::Swap { home, away } => {
+                self.entry(home).swaps += 1;
+                self.entry(away).swaps += 1;
+            }
+            GameEvent::Injury { player, .. } => self.entry(player).injuries += 1,
+            GameEvent::Watch { player } => self.entry(player).watched += 1,
+            GameEvent::Replaced { player } => self.entry(player).replaced = true,
+            GameEvent::Win { player }
+            | GameEvent::Let { player }
+            | GameEvent::Poach { player }
+            | GameEvent::Chase { player, .. }
+            | GameEvent::StepUp { player, .. }
+            | GameEvent::PlayingHurt { player, .. }
+            | GameEvent::Recruit { player, .. }
+            | GameEvent::Replacement { player } => {
+                self.entry(player);
+            }
+            GameEvent::GameWon { .. }
+            | GameEvent::SetWon { .. }
+            | GameEvent::Shuffle
+            | GameEvent::WeatherChange { .. } => (),
+        }
+    }
+
+    /// Adds the stats of a finished game, which counts as a match for everyone that was in it
+    pub fn record_game(&mut self, game: &Self) {
+        for (&player, stats) in &game.players {
+            let career = self.entry(player);
+            career.merge(stats);
+            career.matches += 1;
+        }
+    }
+
+    /// Every player with stats, the ones with the most points won first
+    pub fn ranking(&self) -> Vec<PlayerId> {
+        let mut players: Vec<_> = self.players.keys().copied().collect();
+        players.sort_by(|a, b| {
+            let (a, b) = (&self.players[a], &self.players[b]);
+            b.points_won
+                .cmp(&a.points_won)
+                .then(a.points_lost.cmp(&b.points_lost))
+        });
+        players
+    }
+
+    /// A table with the stats of the given players
+    pub fn get_text(&self, players: &[PlayerId], data: &Data) -> String {
+        let mut output = String::new();
+        writeln!(
+            output,
+            "{:<24} {:>4} {:>4} {:>4} {:>3} {:>3} {:>3} {:>5} {:>5} {:>4} {:>4} {:>3} {:>3}",
+            "Player",
+            "M",
+            "Srv",
+            "Ace",
+            "Flt",
+            "DF",
+            "Inj",
+            "Hits",
+            "Miss",
+            "PW",
+            "PL",
+            "Swp",
+            "Wat"
+        )
+        .unwrap();
+
+        for player in players {
+            let stats = self.get(player).copied().unwrap_or_default();
+            writeln!(
+                output,
+                "{:<24} {:>4} {:>4} {:>4} {:>3} {:>3} {:>3} {:>5} {:>5} {:>4} {:>4} {:>3} {:>3}{}",
+                data.get_player(player).unwrap().get_name(),
+                stats.matches,
+                stats.serves,
+                stats.aces,
+                stats.faults,
+                stats.double_faults,
+                stats.injuries,
+                stats.hits,
+                stats.misses,
+                stats.points_won,
+                stats.points_lost,
+                stats.swaps,
+                stats.watched,
+                if stats.replaced { "  Replaced" } else { "" },
+            )
+            .unwrap();
+        }
+
+        output
+    }
+}
+
+pub fn run(options: &Options, player: Option<&str>) {
+    let data = crate::load_league(options);
+    let ledger = data.get_career_stats();
+
+    let players = match player {
+        Some(name) => {
+            let Some(player) = data.find_player(name) else {
+                println!("There's no player called {name} in the league.");
+                return;
+            };
+            vec![player]
+        }
+        None if ledger.players.is_empty() => {
+            println!("Nobody has played a match yet.");
+            return;
+        }
+        None => ledger.ranking(),
+    };
+
+    print!("{}", ledger.get_text(&players, &data));
+}
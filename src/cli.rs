@@ -0,0 +1,309 @@
+use std::{fmt::Display, path::PathBuf, str::FromStr, time::Duration};
+
+use crate::{
+    game::{Court, MatchConfig, Rotation, Scoring},
+    playoffs::Bracket,
+    tournament::System,
+    Kind, KINDS,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum FeedFormat {
+    Text,
+    Jsonl,
+}
+
+impl FromStr for FeedFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "jsonl" => Ok(Self::Jsonl),
+            _ => Err(format!("unknown feed format: {s}")),
+        }
+    }
+}
+
+/// How the reports of a match are shown
+#[derive(Clone, Copy, PartialEq)]
+pub enum Playback {
+    /// Every report, waiting between them. Higher speeds wait less
+    Live(f64),
+    /// Every report, without waiting
+    Instant,
+    /// Only the final report and the box score
+    Headless,
+    /// Nothing at all
+    Quiet,
+}
+
+impl Playback {
+    pub fn delay(self, comment: &str) -> Option<Duration> {
+        match self {
+            Self::Live(speed) => Some(
+                Duration::from_millis((100 + 100 * comment.len()).try_into().unwrap())
+                    .div_f64(speed),
+            ),
+            Self::Instant | Self::Headless | Self::Quiet => None,
+        }
+    }
+}
+
+pub enum Command {
+    /// Play a single match
+    Play { kind: &'static Kind },
+    /// Play many matches between the same two teams and show statistics about them
+    Simulate {
+        matches: usize,
+        /// Uses every core when not given
+        threads: Option<usize>,
+    },
+    /// Play the league's season, starting a new one if there isn't one going
+    Season {
+        /// How many teams a new season should have at least
+        teams: usize,
+        /// How many days to play, all that are left when not given
+        days: Option<usize>,
+    },
+    /// Play a tournament between the best teams of the league's season
+    Playoffs {
+        bracket: Bracket,
+        /// How many games a series can last at most
+        best_of: usize,
+        /// How many teams make it, every team in the season when not given
+        teams: Option<usize>,
+    },
+    /// Show the ratings of every team and player in the league
+    Ratings,
+    /// Show the career stats of the players in the league
+    Stats {
+        /// Only show this player
+        player: Option<String>,
+    },
+    /// Play a Swiss or round-robin tournament between teams of the league
+    Tournament {
+        system: System,
+        /// How many rounds a Swiss tournament lasts
+        rounds: Option<usize>,
+        teams: usize,
+    },
+}
+
+pub struct Options {
+    pub command: Command,
+    pub seed: Option<u64>,
+    pub playback: Playback,
+    pub format: FeedFormat,
+    pub league: Option<PathBuf>,
+    /// Replaces the league's weathers with the ones in this file
+    pub weathers: Option<PathBuf>,
+    pub checkpoint: Option<PathBuf>,
+    pub stop_after: Option<usize>,
+    pub home: String,
+    pub away: String,
+    pub rotation: Rotation,
+    pub scoring: Scoring,
+    pub court: Court,
+}
+
+/// Shown under anything that's wrong with the arguments
+pub const USAGE: &str =
+    "usage: blaseball [play|simulate|season|playoffs|tournament|ratings|stats] [--option value]...";
+
+/// Parses the value that follows `flag`
+fn value<T, I>(args: &mut I, flag: &str, expected: &str) -> Result<T, String>
+where
+    T: FromStr,
+    T::Err: Display,
+    I: Iterator<Item = String>,
+{
+    args.next()
+        .ok_or_else(|| format!("{flag} expects {expected}"))?
+        .parse()
+        .map_err(|err| format!("{flag} expects {expected}: {err}"))
+}
+
+impl Options {
+    /// How the matches the command plays should be played
+    pub const fn get_match_config(&self) -> MatchConfig {
+        MatchConfig {
+            scoring: self.scoring,
+            rotation: self.rotation,
+            court: self.court,
+        }
+    }
+
+    #[allow(clippy::too_many_lines)]
+    pub fn from_args() -> Result<Self, String> {
+        let mut options = Self {
+            command: Command::Play { kind: &KINDS[0] },
+            seed: None,
+            playback: Playback::Live(1.0),
+            format: FeedFormat::Text,
+            league: None,
+            weathers: None,
+            checkpoint: None,
+            stop_after: None,
+            home: "The Speedles".to_owned(),
+            away: "The Spabbles".to_owned(),
+            rotation: Rotation::Point,
+            scoring: Scoring::Short,
+            court: Court::CLASSIC,
+        };
+
+        let mut args = std::env::args().skip(1).peekable();
+
+        if let Some(command) = args.next_if(|x| !x.starts_with("--")) {
+            options.command = match command.as_str() {
+                "play" => Command::Play { kind: &KINDS[0] },
+                "simulate" => Command::Simulate {
+                    matches: 1000,
+                    threads: None,
+                },
+                "season" => Command::Season {
+                    teams: 8,
+                    days: None,
+                },
+                "playoffs" => Command::Playoffs {
+                    bracket: Bracket::Single,
+                    best_of: 1,
+                    teams: None,
+                },
+                "ratings" => Command::Ratings,
+                "stats" => Command::Stats { player: None },
+                "tournament" => Command::Tournament {
+                    system: System::Swiss,
+                    rounds: None,
+                    teams: 8,
+                },
+                _ => return Err(format!("unknown command: {command}")),
+            };
+        }
+
+        let mut rotation_given = false;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--seed" => options.seed = Some(value(&mut args, &arg, "a number")?),
+                "--no-delay" => options.playback = Playback::Instant,
+                "--headless" => options.playback = Playback::Headless,
+                "--quiet" => options.playback = Playback::Quiet,
+                "--speed" => {
+                    let speed = value(&mut args, &arg, "a positive multiplier")?;
+                    if speed <= 0.0 {
+                        return Err("--speed expects a positive multiplier".to_owned());
+                    }
+                    options.playback = Playback::Live(speed);
+                }
+                "--format" => options.format = value(&mut args, &arg, "text or jsonl")?,
+                "--league" => options.league = Some(value(&mut args, &arg, "a path")?),
+                "--weathers" => options.weathers = Some(value(&mut args, &arg, "a path")?),
+                "--checkpoint" => options.checkpoint = Some(value(&mut args, &arg, "a path")?),
+                "--stop-after" => {
+                    options.stop_after = Some(value(&mut args, &arg, "a number of ticks")?);
+                }
+                "--home" => options.home = value(&mut args, &arg, "a team name")?,
+                "--away" => options.away = value(&mut args, &arg, "a team name")?,
+                "--matches" => match &mut options.command {
+                    Command::Simulate { matches, .. } => {
+                        *matches = value(&mut args, &arg, "a number of matches")?;
+                    }
+                    _ => return Err("--matches only works with simulate".to_owned()),
+                },
+                "--threads" => match &mut options.command {
+                    Command::Simulate { threads, .. } => {
+                        *threads = Some(value(&mut args, &arg, "a number of threads")?);
+                    }
+                    _ => return Err("--threads only works with simulate".to_owned()),
+                },
+                "--teams" => match &mut options.command {
+                    Command::Season { teams, .. } | Command::Tournament { teams, .. } => {
+                        *teams = value(&mut args, &arg, "a number of teams")?;
+                    }
+                    Command::Playoffs { teams, .. } => {
+                        let count = value(&mut args, &arg, "a number of teams")?;
+                        if count == 0 {
+                            return Err(
+                                "--teams expects at least one team for the playoffs".to_owned()
+                            );
+                        }
+                        *teams = Some(count);
+                    }
+                    _ => {
+                        return Err(
+                            "--teams only works with season, playoffs and tournament".to_owned()
+                        )
+                    }
+                },
+                "--system" => match &mut options.command {
+                    Command::Tournament { system, .. } => {
+                        *system = value(&mut args, &arg, "swiss or round-robin")?;
+                    }
+                    _ => return Err("--system only works with tournament".to_owned()),
+                },
+                "--rounds" => match &mut options.command {
+                    Command::Tournament { rounds, .. } => {
+                        *rounds = Some(value(&mut args, &arg, "a number of rounds")?);
+                    }
+                    _ => return Err("--rounds only works with tournament".to_owned()),
+                },
+                "--bracket" => match &mut options.command {
+                    Command::Playoffs { bracket, .. } => {
+                        *bracket = value(&mut args, &arg, "single or double")?;
+                    }
+                    _ => return Err("--bracket only works with playoffs".to_owned()),
+                },
+                "--series" => match &mut options.command {
+                    Command::Playoffs { best_of, .. } => {
+                        *best_of = value(&mut args, &arg, "an odd number of games")?;
+                        if *best_of % 2 == 0 {
+                            return Err("--series expects an odd number of games".to_owned());
+                        }
+                    }
+                    _ => return Err("--series only works with playoffs".to_owned()),
+                },
+                "--days" => match &mut options.command {
+                    Command::Season { days, .. } => {
+                        *days = Some(value(&mut args, &arg, "a number of days")?);
+                    }
+                    _ => return Err("--days only works with season".to_owned()),
+                },
+                "--player" => match &mut options.command {
+                    Command::Stats { player } => {
+                        *player = Some(value(&mut args, &arg, "a player name")?);
+                    }
+                    _ => return Err("--player only works with stats".to_owned()),
+                },
+                "--kind" => match &mut options.command {
+                    Command::Play { kind } => *kind = value(&mut args, &arg, "a game kind")?,
+                    _ => {
+                        return Err(
+                            "--kind only works with play; seasons, playoffs, tournaments \
+                            and simulations always play singles"
+                                .to_owned(),
+                        )
+                    }
+                },
+                "--rotation" => {
+                    options.rotation = value(&mut args, &arg, "point, serve or rallies:N")?;
+                    rotation_given = true;
+                }
+                "--scoring" => options.scoring = value(&mut args, &arg, "short or best-of-N")?,
+                "--court" => {
+                    options.court = value(&mut args, &arg, "classic, grid or LANESxDEPTHS")?;
+                }
+                _ => return Err(format!("unknown argument: {arg}")),
+            }
+        }
+
+        if let Command::Play { kind } = options.command {
+            if !kind.rotation && rotation_given {
+                return Err(format!(
+                    "--rotation doesn't work with {} games, which keep the same players on court",
+                    kind.name
+                ));
+            }
+        }
+        Ok(options)
+    }
+}
@@ -1,23 +1,102 @@
+mod doubles;
+mod event;
 mod report;
+mod scoring;
 mod states;
+mod summary;
+mod umpire;
 mod weather;
 
-use std::collections::VecDeque;
+use std::{collections::VecDeque, str::FromStr};
 
+pub use doubles::Doubles;
+pub use event::GameEvent;
 use rand::Rng;
-use report::Report;
-use states::{PlayerState, Space};
-use weather::{Weather, WeatherResult};
+pub use report::Report;
+use report::{Moment, PlayerStateSnapshot};
+pub use scoring::Scoring;
+use scoring::{PointOutcome, Scoreboard};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+pub use states::Court;
+use states::{Placement, PlayerState, Space};
+pub use summary::Summary;
+use umpire::Umpire;
+pub use weather::Climate;
+use weather::{Hooks, Venue, Weather, WeatherResult};
+
+use crate::{career::Ledger, player::PlayerId, ratings::PointPlayed, team::TeamId, Data};
+
+/// How often a serve clips the net and has to be served again
+const LET_CHANCE: f64 = 0.02;
+/// How much a player tires with every hit
+const HIT_FATIGUE: f64 = 0.002;
+/// The longest a player can be out injured for
+const MAX_INJURY_DAYS: usize = 5;
+
+/// A kind of game. Adding one to `KINDS` is all it takes to make it playable as a single match;
+/// seasons, playoffs, tournaments and simulations still only play singles
+pub trait Run: Serialize + DeserializeOwned {
+    /// What can happen during the game
+    type Event;
+    /// What the feed gets told about each event
+    type Report: Feed;
+    /// What's left once the game is over
+    type Summary: BoxScore;
+
+    /// Sets up a game between two teams, played the way `config` says
+    fn start<R: Rng>(
+        home: TeamId,
+        away: TeamId,
+        config: MatchConfig,
+        data: &mut Data,
+        rng: &mut R,
+    ) -> Self;
 
-use crate::{player::Player, team::TeamId, Data};
-
-// I plan to support multiple kinds of games so i'm making this a struct
-pub trait Run {
     fn tick<R: Rng>(&mut self, data: &mut Data, rng: &mut R) -> Result;
 
-    fn report(&mut self, comment: String, data: &Data);
+    fn report(&mut self, event: Self::Event, data: &Data);
+
+    /// The oldest report that hasn't been taken yet
+    fn pop_report(&mut self) -> Option<Self::Report>;
+
+    /// How the game went, for the box score
+    fn get_summary(&self) -> Self::Summary;
+}
+
+/// How a match is to be played, which each kind of game follows as far as it can
+#[derive(Clone, Copy, Debug)]
+pub struct MatchConfig {
+    pub scoring: Scoring,
+    pub rotation: Rotation,
+    pub court: Court,
+}
+
+/// Anything a game reports can be shown in the feed
+pub trait Feed {
+    /// What the announcer says, which sets how long live playback waits
+    fn get_comment(&self) -> &str;
+
+    fn get_text(&self, data: &Data) -> String;
+
+    /// The report as a single line of JSON
+    fn get_json(&self, data: &Data) -> String;
 }
 
+/// A finished game, as printed after the feed
+pub trait BoxScore {
+    fn get_text(&self, data: &Data) -> String;
+}
+
+/// What a finished game leaves behind for the league's ratings and careers
+pub struct MatchRecord<'a> {
+    pub home: TeamId,
+    pub away: TeamId,
+    pub winner: Side,
+    pub points: &'a [PointPlayed],
+    pub stats: &'a Ledger,
+}
+
+#[derive(Serialize, Deserialize)]
 enum GameState {
     Serving(Side),
     PreHit(Side),
@@ -25,7 +104,7 @@ enum GameState {
     Score(Side),
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum Side {
     Home,
     Away,
@@ -40,38 +119,117 @@ impl Side {
     }
 }
 
+/// When the teams move on to the next player in their lineups
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Rotation {
+    /// After every point
+    Point,
+    /// Whenever the serve passes to the other side
+    ServeGame,
+    /// After the given number of rallies
+    Rallies(usize),
+}
+
+impl FromStr for Rotation {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "point" => Ok(Self::Point),
+            "serve" => Ok(Self::ServeGame),
+            _ => s
+                .strip_prefix("rallies:")
+                .and_then(|x| x.parse().ok())
+                .filter(|&x| x > 0)
+                .map(Self::Rallies)
+                .ok_or_else(|| format!("unknown rotation policy: {s}")),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct Game {
     home: PlayerState,
     away: PlayerState,
     ball_direction: Space,
+    court: Court,
 
     state: GameState,
+    umpire: Umpire,
 
-    reports: VecDeque<Report>,
+    rotation: Rotation,
+    rallies_since_rotation: usize,
+    /// Whether the last hit was too fast to stretch for
+    power_shot: bool,
+    /// Ticks until the ball lands
+    flight: usize,
 
     weather: Weather,
+    /// Reports that haven't been taken yet, oldest first
+    reports: VecDeque<Report>,
 }
 
 impl Game {
-    pub fn pop_report(&mut self) -> Option<Report> {
-        self.reports.pop_back()
-    }
     pub const fn new(home: TeamId, away: TeamId, weather: Weather) -> Self {
         Self {
             home: PlayerState {
                 team: home,
-                space: Space::Middle,
-                score: 0,
+                space: Court::CLASSIC.center(),
             },
             away: PlayerState {
                 team: away,
-                space: Space::Middle,
-                score: 0,
+                space: Court::CLASSIC.center(),
             },
-            ball_direction: Space::Middle,
+            ball_direction: Court::CLASSIC.center(),
+            court: Court::CLASSIC,
             state: GameState::Serving(Side::Home),
-            reports: VecDeque::new(),
+            umpire: Umpire::new(Scoring::Short),
+            rotation: Rotation::Point,
+            rallies_since_rotation: 0,
+            power_shot: false,
+            flight: 0,
             weather,
+            reports: VecDeque::new(),
+        }
+    }
+    pub fn with_scoring(mut self, scoring: Scoring) -> Self {
+        self.umpire.scoreboard = Scoreboard::new(scoring);
+        self
+    }
+    pub const fn with_rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+    pub const fn with_court(mut self, court: Court) -> Self {
+        self.court = court;
+        self
+    }
+    pub const fn get_scoreboard(&self) -> &Scoreboard {
+        &self.umpire.scoreboard
+    }
+    pub const fn get_longest_rally(&self) -> usize {
+        self.umpire.get_longest_rally()
+    }
+    pub const fn get_team_id(&self, side: Side) -> TeamId {
+        self.get_team(side).team
+    }
+    /// The side that's ahead, which after the last tick is the side that won
+    pub const fn get_winner(&self) -> Side {
+        self.umpire.get_winner()
+    }
+    pub fn get_record(&self) -> MatchRecord<'_> {
+        self.umpire.get_record(self.home.team, self.away.team)
+    }
+    /// Ticks the game until it's over, returning every report along the way
+    pub fn play_out<R: Rng>(&mut self, data: &mut Data, rng: &mut R) -> Vec<Report> {
+        let mut reports = vec![];
+        loop {
+            let result = self.tick(data, rng);
+            reports.extend(std::iter::from_fn(|| self.pop_report()));
+
+            if matches!(result, Result::Finished) {
+                break reports;
+            }
         }
     }
     pub const fn get_team(&self, team: Side) -> &PlayerState {
@@ -80,10 +238,39 @@ impl Game {
             Side::Away => &self.away,
         }
     }
-    pub fn get_team_mut(&mut self, team: Side) -> &mut PlayerState {
-        match team {
-            Side::Home => &mut self.home,
-            Side::Away => &mut self.away,
+    /// Sends a substitute in if the player on court for `side` is injured
+    fn field_substitute(&mut self, side: Side, data: &mut Data) {
+        let team = self.get_team(side).team;
+        let player = team.get_current_player(data).unwrap();
+        if let (_, Some(event)) = Umpire::field(team, player, &[player], data) {
+            self.report(event, data);
+        }
+    }
+    fn check_injuries<R: Rng>(&mut self, data: &mut Data, rng: &mut R) {
+        for side in [Side::Home, Side::Away] {
+            let player = self.get_team(side).team.get_current_player(data).unwrap();
+            if let Some(injury) = Umpire::check_injury(player, data, rng) {
+                self.report(injury, data);
+                self.field_substitute(side, data);
+            }
+        }
+    }
+    fn rotate_players(&mut self, data: &mut Data) {
+        self.rallies_since_rotation = 0;
+
+        for side in [Side::Home, Side::Away] {
+            let team_id = self.get_team(side).team;
+            team_id.rotate(data);
+
+            let player = team_id.get_current_player(data).unwrap();
+
+            self.report(
+                GameEvent::StepUp {
+                    player,
+                    team: team_id,
+                },
+                data,
+            );
         }
     }
 }
@@ -94,37 +281,78 @@ pub enum Result {
 }
 
 #[allow(clippy::too_many_lines)]
-// ignore the fact that this is a trait and not a impl on the Game itself. i want to support having multiple kinds of games eventually
 impl Run for Game {
+    type Event = GameEvent;
+    type Report = Report;
+    type Summary = Summary;
+
+    fn start<R: Rng>(
+        home: TeamId,
+        away: TeamId,
+        config: MatchConfig,
+        data: &mut Data,
+        rng: &mut R,
+    ) -> Self {
+        Self::new(home, away, data.get_climate().pick(rng))
+            .with_rotation(config.rotation)
+            .with_scoring(config.scoring)
+            .with_court(config.court)
+    }
+
     fn tick<R: Rng>(&mut self, data: &mut Data, rng: &mut R) -> Result {
         macro_rules! report {
-            ($text:literal) => {
-                self.report(format!($text), data);
-            };
-            ($text:literal, $($player:ident),+) => {
-                self.report(format!($text, $($player),+), data);
+            ($event:expr) => {
+                self.report($event, data);
             };
         }
+        if self.umpire.ticks == 0 {
+            self.field_substitute(Side::Home, data);
+            self.field_substitute(Side::Away, data);
+            self.weather.clone().match_start(self, data, rng);
+        }
+        self.umpire.ticks += 1;
         match self.state {
             GameState::Serving(serving_side) => {
                 let (serving_state, receiving_state) = match serving_side {
                     Side::Home => (&mut self.home, &mut self.away),
                     Side::Away => (&mut self.away, &mut self.home),
                 };
-                serving_state.space = Space::Middle;
-                receiving_state.space = Space::Middle;
-                self.ball_direction = rng.gen();
+                serving_state.space = self.court.center();
+                receiving_state.space = self.court.center();
+                self.umpire.new_point(serving_side);
+                self.power_shot = false;
 
-                let serving_player_name = serving_state
-                    .team
-                    .get_current_player(data)
-                    .and_then(|x| data.get_player(&x))
-                    .map(Player::get_name)
-                    .unwrap();
+                let serving_player = serving_state.team.get_current_player(data).unwrap();
+                let receiving_player = receiving_state.team.get_current_player(data).unwrap();
 
-                report!("{} serves!", serving_player_name);
+                if let Some(call) = self.umpire.call_serve(serving_player, data, rng) {
+                    if matches!(call, GameEvent::DoubleFault { .. }) {
+                        self.state = GameState::Score(serving_side.opposite());
+                    }
+                    report!(call);
+                    return Result::Continue;
+                }
+                // An accurate serve lands as far from the receiver as it can, and if it's hard
+                // enough too the receiver might not get to it at all
+                let (accurate, ace) = Umpire::call_ace(serving_player, receiving_player, data, rng);
+                self.ball_direction = if accurate {
+                    self.court.farthest(&[receiving_state.space], rng)
+                } else {
+                    self.court.random(rng)
+                };
+                self.flight = data.get_player(&serving_player).unwrap().flight_time(false);
+
+                report!(GameEvent::Serve {
+                    player: serving_player
+                });
 
-                self.state = GameState::PreHit(serving_side.opposite());
+                if let Some(ace) = ace {
+                    report!(ace);
+                    self.state = GameState::Score(serving_side);
+                } else {
+                    self.state = GameState::PreHit(serving_side.opposite());
+                }
+                self.weather.clone().serve(serving_side, self, data, rng);
                 Result::Continue
             }
             GameState::PreHit(hitting_side) => {
@@ -134,63 +362,87 @@ impl Run for Game {
                 };
 
                 let hitting_player = hitter_state.team.get_current_player(data).unwrap();
-
-                if hitter_state.space == self.ball_direction {
-                    if data
-                        .get_player(&hitting_player)
-                        .unwrap()
-                        .distraction_check(rng)
-                    {
-                        hitter_state.space = rng.gen();
+                let player = data.get_player(&hitting_player).unwrap();
+                let distance = hitter_state.space.distance(self.ball_direction);
+                self.flight = self.flight.saturating_sub(1);
+
+                // The player runs for the ball while it's in the air, and waits for it once
+                // they're there, which is when their focus can slip
+                if distance > 0 {
+                    let steps = player.steps(self.umpire.rally, rng).min(distance);
+                    for _ in 0..steps {
+                        hitter_state.space = hitter_state.space.toward(self.ball_direction);
                     }
-                } else if data.get_player(&hitting_player).unwrap().speed_check(rng) {
-                    hitter_state.space = self.ball_direction;
-                } else {
-                    hitter_state.space = rng.gen();
+                    if distance > 1 {
+                        report!(GameEvent::Chase {
+                            player: hitting_player,
+                            steps_left: distance - steps,
+                        });
+                    }
+                } else if self.flight == 0 && player.distraction_check(self.umpire.rally, rng) {
+                    hitter_state.space = self.court.random(rng);
                 }
 
-                let weather_result = self.weather.pre_hit(hitting_side, self, data, rng);
-
-                self.state = GameState::Hit(hitting_side, weather_result);
+                if self.flight == 0 {
+                    let weather_result =
+                        self.weather.clone().pre_hit(hitting_side, self, data, rng);
+                    self.state = GameState::Hit(hitting_side, weather_result);
+                }
                 Result::Continue
             }
             GameState::Hit(hitting_side, weather_result) => {
-                let (hitter_state, _) = match hitting_side {
-                    Side::Home => (&mut self.home, &mut self.away),
-                    Side::Away => (&mut self.away, &mut self.home),
-                };
+                let hitter_state = self.get_team(hitting_side);
 
                 let hitting_player = hitter_state.team.get_current_player(data).unwrap();
 
                 match weather_result {
                     WeatherResult::Prevent => {
-                        let hitting_player_name =
-                            data.get_player(&hitting_player).unwrap().get_name();
-                        report!("{} doesn't manage to hit!", hitting_player_name);
-                        self.get_team_mut(hitting_side.opposite()).score += 1;
+                        report!(GameEvent::Prevented {
+                            player: hitting_player
+                        });
 
                         self.state = GameState::Score(hitting_side.opposite());
                         Result::Continue
                     }
                     WeatherResult::Nothing => {
-                        if hitter_state.space == self.ball_direction {
-                            if data.get_player(&hitting_player).unwrap().control_check(rng) {
-                                self.ball_direction = self.ball_direction.farthest(rng);
+                        data.get_player_mut(&hitting_player)
+                            .unwrap()
+                            .tire(HIT_FATIGUE);
+                        let player = data.get_player(&hitting_player).unwrap();
+
+                        // Players can stretch for a ball in the space next to them, unless it
+                        // was hit too hard
+                        let reached = hitter_state.space == self.ball_direction
+                            || (!self.power_shot
+                                && hitter_state.space.is_adjacent(self.ball_direction)
+                                && player.reach_check(self.umpire.rally, rng));
+
+                        if reached {
+                            // A controlled hit goes as far from the other player as it can
+                            let receiver = self.get_team(hitting_side.opposite()).space;
+                            if player.control_check(self.umpire.rally, rng) {
+                                self.ball_direction = self.court.farthest(&[receiver], rng);
                             } else {
-                                self.ball_direction = rng.gen();
+                                self.ball_direction = self.court.random(rng);
                             }
-
-                            let hitting_player_name =
-                                data.get_player(&hitting_player).unwrap().get_name();
-
-                            report!("{hitting_player_name} hits!");
+                            self.power_shot = player.power_check(rng);
+                            self.flight = player.flight_time(self.power_shot);
+
+                            self.umpire.count_hit();
+
+                            let player = hitting_player;
+                            report!(match Placement::new(receiver, self.ball_direction) {
+                                _ if self.power_shot => GameEvent::PowerShot { player },
+                                Placement::Lob => GameEvent::Lob { player },
+                                Placement::DropShot => GameEvent::DropShot { player },
+                                Placement::Normal => GameEvent::Hit { player },
+                            });
                             self.state = GameState::PreHit(hitting_side.opposite());
+                            self.weather.clone().post_hit(hitting_side, self, data, rng);
                         } else {
-                            let hitting_player_name =
-                                data.get_player(&hitting_player).unwrap().get_name();
-
-                            report!("{hitting_player_name} fails to hit it!");
-                            self.get_team_mut(hitting_side.opposite()).score += 1;
+                            report!(GameEvent::Miss {
+                                player: hitting_player
+                            });
 
                             self.state = GameState::Score(hitting_side.opposite());
                         }
@@ -199,39 +451,107 @@ impl Run for Game {
                 }
             }
             GameState::Score(scoring_side) => {
-                let (scorer_state, _) = match scoring_side {
-                    Side::Home => (&mut self.home, &mut self.away),
-                    Side::Away => (&mut self.away, &mut self.home),
+                let scoring_team = self.get_team(scoring_side).team;
+                let scoring_player = scoring_team.get_current_player(data).unwrap();
+                let losing_team = self.get_team(scoring_side.opposite()).team;
+                let point = PointPlayed {
+                    winner: scoring_player,
+                    winner_team: scoring_team,
+                    loser: losing_team.get_current_player(data).unwrap(),
+                    loser_team: losing_team,
                 };
 
-                let scoring_player_name = scorer_state
-                    .team
-                    .get_current_player(data)
-                    .and_then(|x| data.get_player(&x))
-                    .unwrap()
-                    .get_name();
+                let outcome = self.umpire.award_point(scoring_side, point);
+                report!(GameEvent::Score {
+                    player: scoring_player
+                });
+                self.weather.clone().score(scoring_side, self, data, rng);
+
+                if matches!(outcome, PointOutcome::Match) {
+                    report!(GameEvent::Win {
+                        player: scoring_player
+                    });
+                    self.weather
+                        .clone()
+                        .match_end(scoring_side, self, data, rng);
+                    data.record_game(&self.get_record());
+                    return Result::Finished;
+                }
+                if let Some(event) = self.umpire.announce(outcome, scoring_side, scoring_team) {
+                    report!(event);
+                }
+
+                self.check_injuries(data, rng);
 
-                report!("{scoring_player_name} scores!");
+                let next_server = self.umpire.scoreboard.next_server(scoring_side);
+                self.rallies_since_rotation += 1;
 
-                let (scorer_state, other_state) = match scoring_side {
-                    Side::Home => (&mut self.home, &mut self.away),
-                    Side::Away => (&mut self.away, &mut self.home),
+                let rotate = match self.rotation {
+                    Rotation::Point => true,
+                    Rotation::ServeGame => next_server != self.umpire.server,
+                    Rotation::Rallies(rallies) => self.rallies_since_rotation >= rallies,
                 };
-
-                if scorer_state.score > 4
-                    && (other_state.score < 4 || scorer_state.score > other_state.score + 1)
-                {
-                    report!("{scoring_player_name} wins!");
-                    return Result::Finished;
+                if rotate {
+                    self.rotate_players(data);
                 }
-                self.state = GameState::Serving(scoring_side.opposite());
+
+                self.state = GameState::Serving(next_server);
                 Result::Continue
             }
         }
     }
 
-    fn report(&mut self, comment: String, data: &Data) {
-        let report = Report::take_snapshot(self, data).with_comment(comment);
-        self.reports.push_front(report);
+    fn report(&mut self, event: GameEvent, data: &Data) {
+        self.umpire.record(&event);
+        let snapshot = |state: &PlayerState| PlayerStateSnapshot {
+            team: state.team,
+            player: state.team.get_current_player(data).unwrap(),
+            space: state.space,
+        };
+        let report = Report {
+            home: snapshot(&self.home),
+            away: snapshot(&self.away),
+            moment: Moment {
+                tick: self.umpire.ticks,
+                rally: self.umpire.rally,
+                ball_direction: Some(self.ball_direction),
+                comment: event.get_text(data),
+                event,
+                weather: self.weather.clone(),
+                scoreboard: self.umpire.scoreboard.clone(),
+            },
+        };
+        self.reports.push_back(report);
+    }
+
+    fn pop_report(&mut self) -> Option<Report> {
+        self.reports.pop_front()
+    }
+
+    fn get_summary(&self) -> Self::Summary {
+        self.umpire.get_summary(self.home.team, self.away.team)
+    }
+}
+
+/// Weather falls on whoever is up for each team
+impl Venue for Game {
+    fn get_team_id(&self, side: Side) -> TeamId {
+        self.get_team(side).team
+    }
+
+    fn get_current_player(&self, side: Side, data: &Data) -> PlayerId {
+        self.get_team(side).team.get_current_player(data).unwrap()
+    }
+
+    fn replace_player(&mut self, side: Side, player: PlayerId, data: &mut Data) {
+        *self
+            .get_team(side)
+            .team
+            .get_current_player_mut(data)
+            .unwrap() = player;
+    }
+
+    fn set_weather(&mut self, weather: Weather) {
+        self.weather = weather;
     }
 }
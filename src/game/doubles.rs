@@ -0,0 +1,534 @@
+use std::collections::VecDeque;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{player::PlayerId, ratings::PointPlayed, team::TeamId, Data};
+
+use super::{
+    Court, Feed, GameEvent, GameState, Hooks, MatchConfig, MatchRecord, Moment, Placement,
+    PlayerStateSnapshot, PointOutcome, Result, Run, Scoreboard, Scoring, Side, Space, Summary,
+    Umpire, Venue, Weather, WeatherResult, HIT_FATIGUE,
+};
+
+/// One side of a doubles match, two players sharing the court
+#[derive(Serialize, Deserialize)]
+struct Pair {
+    team: TeamId,
+    players: [PlayerId; 2],
+    spaces: [Space; 2],
+    /// Which of the two serves the next time this side serves
+    server: usize,
+    /// Which of the two is up at the net, ready to poach, if either
+    net: Option<usize>,
+    /// Which of the two touched the ball last
+    last_hitter: usize,
+}
+
+impl Pair {
+    /// The team's current player and the next one in the lineup, injured players are only
+    /// swapped out once the match starts
+    fn field(team: TeamId, court: Court, data: &mut Data) -> Self {
+        let first = team.get_current_player(data).unwrap();
+        let second = team.next_player(&[first], data).unwrap();
+
+        Self {
+            team,
+            players: [first, second],
+            spaces: Self::sides(court),
+            server: 0,
+            net: None,
+            last_hitter: 0,
+        }
+    }
+
+    /// Either side of the court at the baseline, where every point starts
+    const fn sides(court: Court) -> [Space; 2] {
+        let depth = court.baseline();
+        [
+            Space { lane: 0, depth },
+            Space {
+                lane: court.get_lanes() - 1,
+                depth,
+            },
+        ]
+    }
+
+    const fn reset(&mut self, court: Court) {
+        self.spaces = Self::sides(court);
+        self.net = None;
+    }
+
+    /// Whoever didn't hit the ball moves across to cover the lanes their partner left open
+    fn cover<R: Rng>(&mut self, hitter: usize, court: Court, rng: &mut R) {
+        let last = court.get_lanes() - 1;
+        let lane = self.spaces[hitter].lane;
+        let partner = &mut self.spaces[1 - hitter];
+        partner.lane = if last - lane == lane {
+            if rng.gen() {
+                0
+            } else {
+                last
+            }
+        } else {
+            last - lane
+        };
+    }
+}
+
+/// Two players a side. The player who isn't serving or receiving waits at the net and can
+/// cut in on the ball, and partners cover for each other as they move
+#[derive(Serialize, Deserialize)]
+pub struct Doubles {
+    home: Pair,
+    away: Pair,
+    ball_direction: Space,
+    court: Court,
+
+    state: GameState,
+    umpire: Umpire,
+
+    /// Whether the last hit was too fast to stretch for
+    power_shot: bool,
+    /// Which player of the side about to hit is going for the ball, once it's settled
+    hitter: Option<usize>,
+    /// Whether the player going for the ball cut in at the net
+    poached: bool,
+    /// Ticks until the ball lands
+    flight: usize,
+    weather: Weather,
+    /// Reports that haven't been taken yet, oldest first
+    reports: VecDeque<DoublesReport>,
+}
+
+/// What a doubles match reports about each event, with both players on each side
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DoublesReport {
+    home: [PlayerStateSnapshot; 2],
+    away: [PlayerStateSnapshot; 2],
+    moment: Moment,
+}
+
+impl Feed for DoublesReport {
+    fn get_comment(&self) -> &str {
+        &self.moment.comment
+    }
+
+    fn get_text(&self, data: &Data) -> String {
+        self.moment.get_text([&self.home, &self.away], data)
+    }
+
+    fn get_json(&self, data: &Data) -> String {
+        self.moment.get_json([&self.home, &self.away], data)
+    }
+}
+
+impl Doubles {
+    pub fn new(
+        home: TeamId,
+        away: TeamId,
+        weather: Weather,
+        court: Court,
+        data: &mut Data,
+    ) -> Self {
+        Self {
+            home: Pair::field(home, court, data),
+            away: Pair::field(away, court, data),
+            ball_direction: court.center(),
+            court,
+            state: GameState::Serving(Side::Home),
+            umpire: Umpire::new(Scoring::Short),
+            power_shot: false,
+            hitter: None,
+            poached: false,
+            flight: 0,
+            weather,
+            reports: VecDeque::new(),
+        }
+    }
+    pub fn with_scoring(mut self, scoring: Scoring) -> Self {
+        self.umpire.scoreboard = Scoreboard::new(scoring);
+        self
+    }
+    const fn get_pair(&self, side: Side) -> &Pair {
+        match side {
+            Side::Home => &self.home,
+            Side::Away => &self.away,
+        }
+    }
+    const fn get_pair_mut(&mut self, side: Side) -> &mut Pair {
+        match side {
+            Side::Home => &mut self.home,
+            Side::Away => &mut self.away,
+        }
+    }
+    /// Which of the pair on `side` the ball is with: whoever is going for it, or else whoever
+    /// touched it last
+    fn get_up(&self, side: Side) -> usize {
+        match (&self.state, self.hitter) {
+            (&GameState::PreHit(hitting_side), Some(hitter)) if hitting_side == side => hitter,
+            _ => self.get_pair(side).last_hitter,
+        }
+    }
+    pub fn get_record(&self) -> MatchRecord<'_> {
+        self.umpire.get_record(self.home.team, self.away.team)
+    }
+    /// Sends a substitute in for the player at `index` on `side` if they're injured, who can't
+    /// be their partner
+    fn field_substitute(&mut self, side: Side, index: usize, data: &mut Data) {
+        let pair = self.get_pair(side);
+        let (team, players) = (pair.team, pair.players);
+        let (player, event) = Umpire::field(team, players[index], &players, data);
+        self.get_pair_mut(side).players[index] = player;
+        if let Some(event) = event {
+            self.report(event, data);
+        }
+    }
+    /// All four players on court might get hurt after a point
+    fn check_injuries<R: Rng>(&mut self, data: &mut Data, rng: &mut R) {
+        for side in [Side::Home, Side::Away] {
+            for index in 0..2 {
+                let player = self.get_pair(side).players[index];
+                if let Some(injury) = Umpire::check_injury(player, data, rng) {
+                    self.report(injury, data);
+                    self.field_substitute(side, index, data);
+                }
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_lines)]
+impl Run for Doubles {
+    type Event = GameEvent;
+    type Report = DoublesReport;
+    type Summary = Summary;
+
+    /// Pairs stay together for the whole match, so there's no rotation
+    fn start<R: Rng>(
+        home: TeamId,
+        away: TeamId,
+        config: MatchConfig,
+        data: &mut Data,
+        rng: &mut R,
+    ) -> Self {
+        let weather = data.get_climate().pick(rng);
+        Self::new(home, away, weather, config.court, data).with_scoring(config.scoring)
+    }
+
+    fn tick<R: Rng>(&mut self, data: &mut Data, rng: &mut R) -> Result {
+        macro_rules! report {
+            ($event:expr) => {
+                self.report($event, data);
+            };
+        }
+        if self.umpire.ticks == 0 {
+            for side in [Side::Home, Side::Away] {
+                self.field_substitute(side, 0, data);
+                self.field_substitute(side, 1, data);
+            }
+            self.weather.clone().match_start(self, data, rng);
+        }
+        self.umpire.ticks += 1;
+        match self.state {
+            GameState::Serving(serving_side) => {
+                self.home.reset(self.court);
+                self.away.reset(self.court);
+                self.umpire.new_point(serving_side);
+                self.power_shot = false;
+                self.poached = false;
+
+                // The server's partner waits at the net
+                let serving_pair = self.get_pair_mut(serving_side);
+                let server_index = serving_pair.server;
+                serving_pair.net = Some(1 - server_index);
+                serving_pair.spaces[1 - server_index].depth = 0;
+                serving_pair.last_hitter = server_index;
+                let serving_player = serving_pair.players[server_index];
+
+                let receiving_pair = self.get_pair_mut(serving_side.opposite());
+                receiving_pair.last_hitter = receiving_pair.server;
+                let receiving_player = receiving_pair.players[receiving_pair.server];
+
+                if let Some(call) = self.umpire.call_serve(serving_player, data, rng) {
+                    if matches!(call, GameEvent::DoubleFault { .. }) {
+                        self.state = GameState::Score(serving_side.opposite());
+                    }
+                    report!(call);
+                    return Result::Continue;
+                }
+                // An accurate serve splits the two receivers down the middle, and if it's hard
+                // enough too the receiver might not get to it at all
+                let (accurate, ace) = Umpire::call_ace(serving_player, receiving_player, data, rng);
+                self.ball_direction = if accurate {
+                    self.court.center()
+                } else {
+                    self.court.random(rng)
+                };
+                self.flight = data.get_player(&serving_player).unwrap().flight_time(false);
+
+                report!(GameEvent::Serve {
+                    player: serving_player
+                });
+                if let Some(ace) = ace {
+                    report!(ace);
+                    self.state = GameState::Score(serving_side);
+                } else {
+                    self.state = GameState::PreHit(serving_side.opposite());
+                }
+                self.weather.clone().serve(serving_side, self, data, rng);
+                Result::Continue
+            }
+            GameState::PreHit(hitting_side) => {
+                let (ball, rally, power_shot) =
+                    (self.ball_direction, self.umpire.rally, self.power_shot);
+                let court = self.court;
+                let pair = match hitting_side {
+                    Side::Home => &mut self.home,
+                    Side::Away => &mut self.away,
+                };
+
+                // Who goes for the ball is settled as soon as it's hit
+                let mut poacher = None;
+                let hitter = *self.hitter.get_or_insert_with(|| {
+                    if let Some(index) = pair.spaces.iter().position(|&x| x == ball) {
+                        // The partner at the net can cut in on anything that isn't hit too hard
+                        match pair.net.filter(|&net| {
+                            net != index
+                                && !power_shot
+                                && data
+                                    .get_player(&pair.players[net])
+                                    .unwrap()
+                                    .reach_check(rally, rng)
+                        }) {
+                            Some(net) => {
+                                pair.spaces[net] = ball;
+                                poacher = Some(pair.players[net]);
+                                net
+                            }
+                            None => index,
+                        }
+                    } else {
+                        // Nobody's there, so the closest goes for it, the player at the net
+                        // if they're just as close
+                        let order = pair.net.map_or([0, 1], |net| [net, 1 - net]);
+                        order
+                            .into_iter()
+                            .min_by_key(|&index| pair.spaces[index].distance(ball))
+                            .unwrap()
+                    }
+                });
+
+                let hitting_player = pair.players[hitter];
+                let player = data.get_player(&hitting_player).unwrap();
+                let distance = pair.spaces[hitter].distance(ball);
+                self.flight = self.flight.saturating_sub(1);
+
+                let mut chase = None;
+                if distance > 0 {
+                    let steps = player.steps(rally, rng).min(distance);
+                    for _ in 0..steps {
+                        pair.spaces[hitter] = pair.spaces[hitter].toward(ball);
+                    }
+                    if distance > 1 {
+                        chase = Some(distance - steps);
+                    }
+                } else if self.flight == 0 && player.distraction_check(rally, rng) {
+                    pair.spaces[hitter] = court.random(rng);
+                }
+
+                if let Some(player) = poacher {
+                    self.poached = true;
+                    report!(GameEvent::Poach { player });
+                }
+                if let Some(steps_left) = chase {
+                    report!(GameEvent::Chase {
+                        player: hitting_player,
+                        steps_left,
+                    });
+                }
+
+                if self.flight == 0 {
+                    let weather_result =
+                        self.weather.clone().pre_hit(hitting_side, self, data, rng);
+                    self.state = GameState::Hit(hitting_side, weather_result);
+                }
+                Result::Continue
+            }
+            GameState::Hit(hitting_side, weather_result) => {
+                let hitter = self.hitter.take().unwrap();
+                let pair = self.get_pair_mut(hitting_side);
+                pair.last_hitter = hitter;
+                let hitting_player = pair.players[hitter];
+                let space = pair.spaces[hitter];
+
+                if matches!(weather_result, WeatherResult::Prevent) {
+                    report!(GameEvent::Prevented {
+                        player: hitting_player
+                    });
+                    self.state = GameState::Score(hitting_side.opposite());
+                    return Result::Continue;
+                }
+
+                data.get_player_mut(&hitting_player)
+                    .unwrap()
+                    .tire(HIT_FATIGUE);
+                let player = data.get_player(&hitting_player).unwrap();
+
+                let reached = space == self.ball_direction
+                    || (!self.power_shot
+                        && space.is_adjacent(self.ball_direction)
+                        && player.reach_check(self.umpire.rally, rng));
+
+                if reached {
+                    // A controlled hit goes as far from both of the other players as it can
+                    let receivers = self.get_pair(hitting_side.opposite()).spaces;
+                    if player.control_check(self.umpire.rally, rng) {
+                        self.ball_direction = self.court.farthest(&receivers, rng);
+                    } else {
+                        self.ball_direction = self.court.random(rng);
+                    }
+                    // A volley cut off at the net is always too quick to stretch for
+                    self.power_shot = self.poached || player.power_check(rng);
+                    self.poached = false;
+                    self.flight = player.flight_time(self.power_shot);
+
+                    let court = self.court;
+                    let pair = self.get_pair_mut(hitting_side);
+                    pair.net.get_or_insert(1 - hitter);
+                    pair.cover(hitter, court, rng);
+
+                    self.umpire.count_hit();
+
+                    let ball = self.ball_direction;
+                    let receiver = *receivers
+                        .iter()
+                        .min_by_key(|space| space.distance(ball))
+                        .unwrap();
+                    let player = hitting_player;
+                    report!(match Placement::new(receiver, ball) {
+                        _ if self.power_shot => GameEvent::PowerShot { player },
+                        Placement::Lob => GameEvent::Lob { player },
+                        Placement::DropShot => GameEvent::DropShot { player },
+                        Placement::Normal => GameEvent::Hit { player },
+                    });
+                    self.state = GameState::PreHit(hitting_side.opposite());
+                    self.weather.clone().post_hit(hitting_side, self, data, rng);
+                } else {
+                    report!(GameEvent::Miss {
+                        player: hitting_player
+                    });
+
+                    self.state = GameState::Score(hitting_side.opposite());
+                }
+                Result::Continue
+            }
+            GameState::Score(scoring_side) => {
+                let scoring_pair = self.get_pair(scoring_side);
+                let losing_pair = self.get_pair(scoring_side.opposite());
+                let scoring_team = scoring_pair.team;
+                let scoring_player = scoring_pair.players[scoring_pair.last_hitter];
+                let point = PointPlayed {
+                    winner: scoring_player,
+                    winner_team: scoring_team,
+                    loser: losing_pair.players[losing_pair.last_hitter],
+                    loser_team: losing_pair.team,
+                };
+
+                let outcome = self.umpire.award_point(scoring_side, point);
+                report!(GameEvent::Score {
+                    player: scoring_player
+                });
+                self.weather.clone().score(scoring_side, self, data, rng);
+
+                if matches!(outcome, PointOutcome::Match) {
+                    report!(GameEvent::Win {
+                        player: scoring_player
+                    });
+                    self.weather
+                        .clone()
+                        .match_end(scoring_side, self, data, rng);
+                    data.record_game(&self.get_record());
+                    return Result::Finished;
+                }
+                if let Some(event) = self.umpire.announce(outcome, scoring_side, scoring_team) {
+                    report!(event);
+                }
+
+                self.check_injuries(data, rng);
+
+                // Partners take turns serving whenever the serve comes back to their side
+                let next_server = self.umpire.scoreboard.next_server(scoring_side);
+                if next_server != self.umpire.server {
+                    let pair = self.get_pair_mut(self.umpire.server);
+                    pair.server = 1 - pair.server;
+
+                    let pair = self.get_pair(next_server);
+                    let (player, team) = (pair.players[pair.server], pair.team);
+                    report!(GameEvent::StepUp { player, team });
+                }
+
+                self.state = GameState::Serving(next_server);
+                Result::Continue
+            }
+        }
+    }
+
+    fn report(&mut self, event: GameEvent, data: &Data) {
+        self.umpire.record(&event);
+        let snapshot = |side: Side, index: usize| {
+            let pair = self.get_pair(side);
+            PlayerStateSnapshot {
+                team: pair.team,
+                player: pair.players[index],
+                space: pair.spaces[index],
+            }
+        };
+        let report = DoublesReport {
+            home: [snapshot(Side::Home, 0), snapshot(Side::Home, 1)],
+            away: [snapshot(Side::Away, 0), snapshot(Side::Away, 1)],
+            moment: Moment {
+                tick: self.umpire.ticks,
+                rally: self.umpire.rally,
+                ball_direction: Some(self.ball_direction),
+                comment: event.get_text(data),
+                event,
+                weather: self.weather.clone(),
+                scoreboard: self.umpire.scoreboard.clone(),
+            },
+        };
+        self.reports.push_back(report);
+    }
+
+    fn pop_report(&mut self) -> Option<DoublesReport> {
+        self.reports.pop_front()
+    }
+
+    fn get_summary(&self) -> Self::Summary {
+        self.umpire.get_summary(self.home.team, self.away.team)
+    }
+}
+
+/// Weather falls on whichever of the pair the ball is with
+impl Venue for Doubles {
+    fn get_team_id(&self, side: Side) -> TeamId {
+        self.get_pair(side).team
+    }
+
+    fn get_current_player(&self, side: Side, _: &Data) -> PlayerId {
+        self.get_pair(side).players[self.get_up(side)]
+    }
+
+    fn replace_player(&mut self, side: Side, player: PlayerId, data: &mut Data) {
+        let up = self.get_up(side);
+        let pair = self.get_pair_mut(side);
+        let old = std::mem::replace(&mut pair.players[up], player);
+        data.get_team_mut(&pair.team)
+            .unwrap()
+            .replace_player(old, player);
+    }
+
+    fn set_weather(&mut self, weather: Weather) {
+        self.weather = weather;
+    }
+}
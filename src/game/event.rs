@@ -0,0 +1,216 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{player::PlayerId, team::TeamId, Data};
+
+use super::Weather;
+
+/// Something that happened during a game. Every report carries one
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum GameEvent {
+    Serve {
+        player: PlayerId,
+    },
+    /// The serve clipped the net, so it's served again
+    Let {
+        player: PlayerId,
+    },
+    /// The first serve went out, the server gets another try
+    Fault {
+        player: PlayerId,
+    },
+    /// Both serves went out, so the point goes to the receiver
+    DoubleFault {
+        player: PlayerId,
+    },
+    /// The receiver couldn't get to the serve
+    Ace {
+        player: PlayerId,
+        receiver: PlayerId,
+    },
+    Hit {
+        player: PlayerId,
+    },
+    /// The player is running for a ball that's far away
+    Chase {
+        player: PlayerId,
+        steps_left: usize,
+    },
+    /// In doubles, the player at the net cut in to take a ball meant for their partner
+    Poach {
+        player: PlayerId,
+    },
+    /// A hit over the head of a player near the net
+    Lob {
+        player: PlayerId,
+    },
+    /// A hit just over the net, in front of a player near the baseline
+    DropShot {
+        player: PlayerId,
+    },
+    /// A hit too fast for the other player to stretch for
+    PowerShot {
+        player: PlayerId,
+    },
+    Miss {
+        player: PlayerId,
+    },
+    /// The weather didn't let the player hit
+    Prevented {
+        player: PlayerId,
+    },
+    Score {
+        player: PlayerId,
+    },
+    GameWon {
+        team: TeamId,
+    },
+    SetWon {
+        team: TeamId,
+        games: usize,
+        other_games: usize,
+    },
+    Win {
+        player: PlayerId,
+    },
+    /// A team moved on to the next player in its lineup
+    StepUp {
+        player: PlayerId,
+        team: TeamId,
+    },
+    /// The two players on court traded teams
+    Swap {
+        home: PlayerId,
+        away: PlayerId,
+    },
+    /// Both lineups got shuffled
+    Shuffle,
+    /// The player got hurt and has to sit out for a few days
+    Injury {
+        player: PlayerId,
+        days: usize,
+    },
+    /// Everybody else in the lineup is injured too, so the player has to play on hurt
+    PlayingHurt {
+        player: PlayerId,
+        team: TeamId,
+    },
+    /// The player was taken out of the league for good
+    Replaced {
+        player: PlayerId,
+    },
+    /// A brand new player took the place of the one that was replaced
+    Replacement {
+        player: PlayerId,
+    },
+    /// The player was watched, which does nothing at all
+    Watch {
+        player: PlayerId,
+    },
+    /// Someone came down from the stands to play for the team
+    Recruit {
+        player: PlayerId,
+        team: TeamId,
+    },
+    WeatherChange {
+        weather: Weather,
+    },
+}
+
+impl GameEvent {
+    /// The name of the weather effect behind this event, if there is one
+    pub const fn weather_effect(&self) -> Option<&'static str> {
+        match self {
+            Self::Swap { .. } => Some("Swap"),
+            Self::Shuffle => Some("Shuffle"),
+            Self::Replaced { .. } => Some("Replace player"),
+            Self::Watch { .. } => Some("Watch"),
+            Self::Recruit { .. } => Some("Recruit"),
+            Self::WeatherChange { .. } => Some("Weather change"),
+            _ => None,
+        }
+    }
+
+    /// Whether this event moved players onto, off or between the teams
+    pub const fn is_roster_change(&self) -> bool {
+        matches!(
+            self,
+            Self::Swap { .. }
+                | Self::Shuffle
+                | Self::Injury { .. }
+                | Self::Replaced { .. }
+                | Self::Replacement { .. }
+                | Self::Recruit { .. }
+        )
+    }
+
+    pub fn get_text(&self, data: &Data) -> String {
+        let player_name = |id: &PlayerId| data.get_player(id).unwrap().get_name().to_owned();
+        let team_name = |id: &TeamId| data.get_team(id).unwrap().get_name().to_owned();
+
+        match self {
+            Self::Serve { player } => format!("{} serves!", player_name(player)),
+            Self::Let { player } => format!("Let! {} serves again.", player_name(player)),
+            Self::Fault { player } => format!("{} faults!", player_name(player)),
+            Self::DoubleFault { player } => format!("{} double faults!", player_name(player)),
+            Self::Ace { player, receiver } => format!(
+                "{} serves an ace past {}!",
+                player_name(player),
+                player_name(receiver)
+            ),
+            Self::Hit { player } => format!("{} hits!", player_name(player)),
+            Self::Chase {
+                player,
+                steps_left: 0,
+            } => format!("{} chases it down!", player_name(player)),
+            Self::Chase { player, steps_left } => format!(
+                "{} runs for it, {steps_left} step{} to go!",
+                player_name(player),
+                if *steps_left == 1 { "" } else { "s" }
+            ),
+            Self::Poach { player } => format!("{} poaches at the net!", player_name(player)),
+            Self::Lob { player } => format!("{} lobs it over!", player_name(player)),
+            Self::DropShot { player } => format!("{} drops it short!", player_name(player)),
+            Self::PowerShot { player } => format!("{} smashes it!", player_name(player)),
+            Self::Miss { player } => format!("{} fails to hit it!", player_name(player)),
+            Self::Prevented { player } => format!("{} doesn't manage to hit!", player_name(player)),
+            Self::Score { player } => format!("{} scores!", player_name(player)),
+            Self::GameWon { team } => format!("{} take the game!", team_name(team)),
+            Self::SetWon {
+                team,
+                games,
+                other_games,
+            } => format!("{} take the set {games}-{other_games}!", team_name(team)),
+            Self::Win { player } => format!("{} wins!", player_name(player)),
+            Self::StepUp { player, team } => {
+                format!("{} steps up for {}.", player_name(player), team_name(team))
+            }
+            Self::Swap { home, away } => format!(
+                "{} and {} swap teams!",
+                player_name(home),
+                player_name(away)
+            ),
+            Self::Injury { player, days } => format!(
+                "{} is hurt! They'll be out for {days} day{}.",
+                player_name(player),
+                if *days == 1 { "" } else { "s" }
+            ),
+            Self::PlayingHurt { player, team } => format!(
+                "Nobody on {} can take over, so {} plays on hurt!",
+                team_name(team),
+                player_name(player)
+            ),
+            Self::Shuffle => "Both lineups get shuffled!".to_owned(),
+            Self::Replaced { player } => format!("{} leaves the league.", player_name(player)),
+            Self::Replacement { player } => {
+                format!("{} takes their place!", player_name(player))
+            }
+            Self::Watch { player } => format!("{} is being watched.", player_name(player)),
+            Self::Recruit { player, team } => format!(
+                "{} comes down from the stands to play for {}!",
+                player_name(player),
+                team_name(team)
+            ),
+            Self::WeatherChange { weather } => weather.get_announcement(data.get_climate()),
+        }
+    }
+}
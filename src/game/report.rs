@@ -1,88 +1,173 @@
 use crate::{player::PlayerId, team::TeamId, Data};
-use std::fmt::Write;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, slice};
 
-use super::{Game, Space, Weather};
+use super::{Feed, GameEvent, Scoreboard, Side, Space, Weather};
 
-#[derive(Debug)]
+/// Rallies this long get called out in the feed
+const LONG_RALLY: usize = 5;
+
+/// What a singles match reports about each event
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Report {
-    home: PlayerStateSnapshot,
-    away: PlayerStateSnapshot,
+    pub(super) home: PlayerStateSnapshot,
+    pub(super) away: PlayerStateSnapshot,
+    pub moment: Moment,
+}
 
-    ball_direction: Option<Space>,
+/// Everything a report says about an event besides who's on court, the same for every kind of
+/// game
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Moment {
+    pub(super) tick: usize,
+    /// Hits since the last serve
+    pub(super) rally: usize,
+    pub(super) ball_direction: Option<Space>,
 
     pub comment: String,
-    weather: Weather,
+    pub event: GameEvent,
+    pub(super) weather: Weather,
+    pub(super) scoreboard: Scoreboard,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct PlayerStateSnapshot {
+    pub(super) team: TeamId,
+    pub(super) player: PlayerId,
+    pub(super) space: Space,
+}
+
+/// A report as it appears in the JSON Lines feed, with names filled in
+#[derive(Serialize)]
+struct FeedEntry<'a> {
+    tick: usize,
+    rally: usize,
+    weather: &'a Weather,
+    home: FeedSide<'a>,
+    away: FeedSide<'a>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    home_partner: Option<FeedSide<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    away_partner: Option<FeedSide<'a>>,
+    ball_direction: Option<Space>,
+    scoreboard: &'a Scoreboard,
+    comment: &'a str,
+    event: &'a GameEvent,
+}
+
+#[derive(Serialize)]
+struct FeedSide<'a> {
     team: TeamId,
+    team_name: &'a str,
     player: PlayerId,
-    score: usize,
+    player_name: &'a str,
+    player_stars: usize,
     space: Space,
 }
 
-impl Report {
-    pub fn take_snapshot(game: &Game, data: &Data) -> Self {
-        let home = PlayerStateSnapshot {
-            team: game.home.team,
-            player: game.home.team.get_current_player(data).unwrap(),
-            score: game.home.score,
-            space: game.home.space,
-        };
-        let away = PlayerStateSnapshot {
-            team: game.away.team,
-            player: game.away.team.get_current_player(data).unwrap(),
-            score: game.away.score,
-            space: game.away.space,
-        };
-
-        let ball_direction = Some(game.ball_direction);
-
-        Self {
-            home,
-            away,
-            ball_direction,
-            comment: String::new(),
-            weather: game.weather,
+impl PlayerStateSnapshot {
+    fn to_feed<'a>(&self, data: &'a Data) -> FeedSide<'a> {
+        FeedSide {
+            team: self.team,
+            team_name: data.get_team(&self.team).unwrap().get_name(),
+            player: self.player,
+            player_name: data.get_player(&self.player).unwrap().get_name(),
+            player_stars: data.get_player(&self.player).unwrap().get_stars(),
+            space: self.space,
         }
     }
+}
 
-    pub fn with_comment(mut self, comment: String) -> Self {
-        self.comment = comment;
-        self
+impl Feed for Report {
+    fn get_comment(&self) -> &str {
+        &self.moment.comment
     }
 
-    pub const fn with_no_ball(mut self) -> Self {
-        self.ball_direction = None;
-        self
+    fn get_text(&self, data: &Data) -> String {
+        self.moment.get_text(
+            [slice::from_ref(&self.home), slice::from_ref(&self.away)],
+            data,
+        )
     }
 
-    pub fn get_text(&self, data: &Data) -> String {
-        let mut output = String::new();
-        writeln!(output, "---------------------[{}]", self.weather).unwrap();
-        writeln!(
-            output,
-            "{}: {}",
-            data.get_player(&self.home.player).unwrap().get_name(),
-            self.home.score,
+    fn get_json(&self, data: &Data) -> String {
+        self.moment.get_json(
+            [slice::from_ref(&self.home), slice::from_ref(&self.away)],
+            data,
         )
-        .unwrap();
+    }
+}
 
+impl Moment {
+    /// The report as the feed shows it, with `sides` being everyone on court for each side
+    pub(super) fn get_text(&self, sides: [&[PlayerStateSnapshot]; 2], data: &Data) -> String {
+        let mut output = String::new();
         writeln!(
             output,
-            "{}: {}",
-            data.get_player(&self.away.player).unwrap().get_name(),
-            self.away.score,
+            "---------------------[{}]",
+            self.weather.get_label(data.get_climate())
         )
         .unwrap();
+        for (side, snapshots) in [Side::Home, Side::Away].into_iter().zip(sides) {
+            let players: Vec<_> = snapshots
+                .iter()
+                .map(|snapshot| {
+                    let player = data.get_player(&snapshot.player).unwrap();
+                    format!("{} {}", player.get_name(), player.get_star_text())
+                })
+                .collect();
+
+            writeln!(
+                output,
+                "{} ({}): {}",
+                players.join(" & "),
+                data.get_team(&snapshots[0].team).unwrap().get_name(),
+                self.scoreboard.get_text(side),
+            )
+            .unwrap();
+        }
 
         writeln!(output).unwrap();
         writeln!(output, "+++++++++++++++++++++++++").unwrap();
         writeln!(output).unwrap();
         writeln!(output, "{}", self.comment).unwrap();
+        if matches!(
+            self.event,
+            GameEvent::Hit { .. }
+                | GameEvent::Lob { .. }
+                | GameEvent::DropShot { .. }
+                | GameEvent::PowerShot { .. }
+        ) && self.rally >= LONG_RALLY
+        {
+            writeln!(
+                output,
+                "The rally goes on, {} hits and counting!",
+                self.rally
+            )
+            .unwrap();
+        }
         writeln!(output, "-------------------------").unwrap();
 
         output
     }
+
+    /// The report as a line of the JSON Lines feed, where a second player on a side is their
+    /// partner
+    pub(super) fn get_json(&self, sides: [&[PlayerStateSnapshot]; 2], data: &Data) -> String {
+        let [home, away] = sides;
+        serde_json::to_string(&FeedEntry {
+            tick: self.tick,
+            rally: self.rally,
+            weather: &self.weather,
+            home: home[0].to_feed(data),
+            away: away[0].to_feed(data),
+            home_partner: home.get(1).map(|x| x.to_feed(data)),
+            away_partner: away.get(1).map(|x| x.to_feed(data)),
+            ball_direction: self.ball_direction,
+            scoreboard: &self.scoreboard,
+            comment: &self.comment,
+            event: &self.event,
+        })
+        .unwrap()
+    }
 }
@@ -0,0 +1,402 @@
+use std::{fmt::Write, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use super::Side;
+
+/// The rule set a match is scored with
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Scoring {
+    /// A single race to five points, winning by two
+    Short,
+    /// Points, games and sets like in tennis
+    Tennis {
+        /// How many sets a side needs to win the match
+        sets_to_win: usize,
+        /// How many games a side needs to win a set
+        games_per_set: usize,
+        /// Whether a set at `games_per_set` all is decided by a tiebreak
+        tiebreak: bool,
+    },
+}
+
+impl Scoring {
+    pub const fn best_of(sets: usize) -> Self {
+        Self::Tennis {
+            sets_to_win: sets / 2 + 1,
+            games_per_set: 6,
+            tiebreak: true,
+        }
+    }
+}
+
+impl FromStr for Scoring {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "short" => Ok(Self::Short),
+            _ => s
+                .strip_prefix("best-of-")
+                .and_then(|x| x.parse::<usize>().ok())
+                .filter(|x| x % 2 == 1)
+                .map(Self::best_of)
+                .ok_or_else(|| format!("unknown scoring rules: {s}")),
+        }
+    }
+}
+
+/// What a point decided
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PointOutcome {
+    Point,
+    Game,
+    Set,
+    Match,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Score {
+    /// Every point won over the whole match
+    pub total: usize,
+    pub points: usize,
+    pub games: usize,
+    pub sets: usize,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Scoreboard {
+    rules: Scoring,
+    home: Score,
+    away: Score,
+    /// Games won by each side in every finished set
+    finished_sets: Vec<(usize, usize)>,
+    tiebreak: bool,
+    /// The side that served the first point of the current game
+    game_server: Side,
+}
+
+impl Scoreboard {
+    pub const fn new(rules: Scoring) -> Self {
+        Self {
+            rules,
+            home: Score {
+                total: 0,
+                points: 0,
+                games: 0,
+                sets: 0,
+            },
+            away: Score {
+                total: 0,
+                points: 0,
+                games: 0,
+                sets: 0,
+            },
+            finished_sets: Vec::new(),
+            tiebreak: false,
+            game_server: Side::Home,
+        }
+    }
+
+    pub const fn get(&self, side: Side) -> &Score {
+        match side {
+            Side::Home => &self.home,
+            Side::Away => &self.away,
+        }
+    }
+
+    const fn get_mut(&mut self, side: Side) -> &mut Score {
+        match side {
+            Side::Home => &mut self.home,
+            Side::Away => &mut self.away,
+        }
+    }
+
+    /// Gives a point to `scorer` and moves the match forward
+    pub fn award_point(&mut self, scorer: Side) -> PointOutcome {
+        self.get_mut(scorer).total += 1;
+
+        let Scoring::Tennis {
+            sets_to_win,
+            games_per_set,
+            tiebreak,
+        } = self.rules
+        else {
+            self.get_mut(scorer).points += 1;
+            let (scorer_score, other_score) = (self.get(scorer), self.get(scorer.opposite()));
+
+            return if scorer_score.points > 4
+                && (other_score.points < 4 || scorer_score.points > other_score.points + 1)
+            {
+                PointOutcome::Match
+            } else {
+                PointOutcome::Point
+            };
+        };
+
+        self.get_mut(scorer).points += 1;
+        let (points, other_points) = (self.get(scorer).points, self.get(scorer.opposite()).points);
+
+        let points_to_win = if self.tiebreak { 7 } else { 4 };
+        if points < points_to_win || points < other_points + 2 {
+            return PointOutcome::Point;
+        }
+
+        self.home.points = 0;
+        self.away.points = 0;
+        self.game_server = self.game_server.opposite();
+        self.get_mut(scorer).games += 1;
+
+        let (games, other_games) = (self.get(scorer).games, self.get(scorer.opposite()).games);
+        let won_set = self.tiebreak || (games >= games_per_set && games >= other_games + 2);
+
+        if !won_set {
+            self.tiebreak = tiebreak && games == games_per_set && other_games == games_per_set;
+            return PointOutcome::Game;
+        }
+
+        self.tiebreak = false;
+        self.finished_sets.push((self.home.games, self.away.games));
+        self.home.games = 0;
+        self.away.games = 0;
+        self.get_mut(scorer).sets += 1;
+
+        if self.get(scorer).sets >= sets_to_win {
+            PointOutcome::Match
+        } else {
+            PointOutcome::Set
+        }
+    }
+
+    /// The side that serves the next point after `last_scorer` won the last one
+    pub const fn next_server(&self, last_scorer: Side) -> Side {
+        if matches!(self.rules, Scoring::Short) {
+            return last_scorer.opposite();
+        }
+
+        if self.tiebreak {
+            // The first point of a tiebreak is served once, then the serve swaps every two points
+            let played = self.home.points + self.away.points;
+            if played.div_ceil(2).is_multiple_of(2) {
+                self.game_server
+            } else {
+                self.game_server.opposite()
+            }
+        } else {
+            self.game_server
+        }
+    }
+
+    /// The final result for each side: points in the short format, sets otherwise
+    pub const fn result(&self) -> (usize, usize) {
+        match self.rules {
+            Scoring::Short => (self.home.points, self.away.points),
+            Scoring::Tennis { .. } => (self.home.sets, self.away.sets),
+        }
+    }
+
+    /// The games each side won in every finished set
+    pub fn get_sets(&self) -> &[(usize, usize)] {
+        &self.finished_sets
+    }
+
+    /// The games won in the last set, from the point of view of `side`
+    pub fn last_set(&self, side: Side) -> Option<(usize, usize)> {
+        self.finished_sets.last().map(|&(home, away)| match side {
+            Side::Home => (home, away),
+            Side::Away => (away, home),
+        })
+    }
+
+    fn point_call(&self, side: Side) -> String {
+        let (points, other_points) = (self.get(side).points, self.get(side.opposite()).points);
+
+        if self.tiebreak || matches!(self.rules, Scoring::Short) {
+            points.to_string()
+        } else if points >= 3 && other_points >= 3 {
+            if points > other_points { "Ad" } else { "40" }.to_owned()
+        } else {
+            ["0", "15", "30", "40"][points].to_owned()
+        }
+    }
+
+    /// One side's line of the scoreboard
+    pub fn get_text(&self, side: Side) -> String {
+        if matches!(self.rules, Scoring::Short) {
+            return self.point_call(side);
+        }
+
+        let mut output = String::new();
+        for &(home, away) in &self.finished_sets {
+            let games = match side {
+                Side::Home => home,
+                Side::Away => away,
+            };
+            write!(output, "{games} ").unwrap();
+        }
+        write!(
+            output,
+            "| {} | {}",
+            self.get(side).games,
+            self.point_call(side)
+        )
+        .unwrap();
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Side::{Away, Home};
+
+    /// Awards every point in order, returning what the last one decided
+    fn play(board: &mut Scoreboard, points: &[Side]) -> PointOutcome {
+        points
+            .iter()
+            .map(|&scorer| board.award_point(scorer))
+            .last()
+            .unwrap()
+    }
+
+    /// Gives `scorer` a game to love
+    fn game(board: &mut Scoreboard, scorer: Side) -> PointOutcome {
+        play(board, &[scorer; 4])
+    }
+
+    fn text(board: &Scoreboard) -> (String, String) {
+        (board.get_text(Home), board.get_text(Away))
+    }
+
+    #[test]
+    fn points_are_called_like_tennis() {
+        let mut board = Scoreboard::new(Scoring::best_of(3));
+        assert_eq!(text(&board), ("| 0 | 0".to_owned(), "| 0 | 0".to_owned()));
+
+        assert_eq!(play(&mut board, &[Home, Home, Away]), PointOutcome::Point);
+        assert_eq!(text(&board), ("| 0 | 30".to_owned(), "| 0 | 15".to_owned()));
+    }
+
+    #[test]
+    fn deuce_goes_to_advantage_and_back() {
+        let mut board = Scoreboard::new(Scoring::best_of(3));
+        play(&mut board, &[Home, Away, Home, Away, Home, Away]);
+        assert_eq!(text(&board), ("| 0 | 40".to_owned(), "| 0 | 40".to_owned()));
+
+        assert_eq!(board.award_point(Home), PointOutcome::Point);
+        assert_eq!(text(&board), ("| 0 | Ad".to_owned(), "| 0 | 40".to_owned()));
+
+        assert_eq!(board.award_point(Away), PointOutcome::Point);
+        assert_eq!(text(&board), ("| 0 | 40".to_owned(), "| 0 | 40".to_owned()));
+
+        assert_eq!(board.award_point(Away), PointOutcome::Point);
+        assert_eq!(text(&board), ("| 0 | 40".to_owned(), "| 0 | Ad".to_owned()));
+
+        assert_eq!(board.award_point(Away), PointOutcome::Game);
+        assert_eq!(text(&board), ("| 0 | 0".to_owned(), "| 1 | 0".to_owned()));
+    }
+
+    #[test]
+    fn the_serve_swaps_every_game() {
+        let mut board = Scoreboard::new(Scoring::best_of(3));
+        // The receiver winning the point doesn't take the serve
+        board.award_point(Away);
+        assert_eq!(board.next_server(Away), Home);
+
+        play(&mut board, &[Home; 4]);
+        assert_eq!(board.next_server(Home), Away);
+        game(&mut board, Away);
+        assert_eq!(board.next_server(Away), Home);
+    }
+
+    #[test]
+    fn six_all_goes_to_a_tiebreak() {
+        let mut board = Scoreboard::new(Scoring::best_of(3));
+        for _ in 0..5 {
+            game(&mut board, Home);
+            game(&mut board, Away);
+        }
+        game(&mut board, Home);
+        assert_eq!(game(&mut board, Away), PointOutcome::Game);
+        assert_eq!(text(&board), ("| 6 | 0".to_owned(), "| 6 | 0".to_owned()));
+
+        // Tiebreak points are counted as they are, and go past 4
+        assert_eq!(play(&mut board, &[Home; 5]), PointOutcome::Point);
+        assert_eq!(text(&board), ("| 6 | 5".to_owned(), "| 6 | 0".to_owned()));
+
+        // Winning by two still counts in a tiebreak
+        assert_eq!(play(&mut board, &[Away; 6]), PointOutcome::Point);
+        assert_eq!(board.award_point(Home), PointOutcome::Point);
+        assert_eq!(text(&board), ("| 6 | 6".to_owned(), "| 6 | 6".to_owned()));
+        assert_eq!(play(&mut board, &[Home, Home]), PointOutcome::Set);
+
+        assert_eq!(board.get_sets(), [(7, 6)]);
+        assert_eq!(board.last_set(Away), Some((6, 7)));
+        assert_eq!(
+            text(&board),
+            ("7 | 0 | 0".to_owned(), "6 | 0 | 0".to_owned())
+        );
+    }
+
+    #[test]
+    fn tiebreaks_are_served_one_point_then_two() {
+        let mut board = Scoreboard::new(Scoring::best_of(3));
+        for _ in 0..6 {
+            game(&mut board, Home);
+            game(&mut board, Away);
+        }
+
+        let mut servers = vec![board.next_server(Away)];
+        for _ in 0..6 {
+            board.award_point(Home);
+            servers.push(board.next_server(Home));
+        }
+        assert_eq!(servers, [Home, Away, Away, Home, Home, Away, Away]);
+    }
+
+    #[test]
+    fn a_set_needs_two_games_more() {
+        let mut board = Scoreboard::new(Scoring::best_of(3));
+        for _ in 0..5 {
+            game(&mut board, Home);
+            game(&mut board, Away);
+        }
+        assert_eq!(game(&mut board, Home), PointOutcome::Game);
+        assert_eq!(game(&mut board, Home), PointOutcome::Set);
+        assert_eq!(board.get_sets(), [(7, 5)]);
+        assert_eq!(board.result(), (1, 0));
+    }
+
+    #[test]
+    fn the_match_ends_with_enough_sets() {
+        let mut board = Scoreboard::new(Scoring::best_of(3));
+        let set =
+            |board: &mut Scoreboard, scorer| (0..6).map(|_| game(board, scorer)).last().unwrap();
+
+        assert_eq!(set(&mut board, Home), PointOutcome::Set);
+        assert_eq!(set(&mut board, Away), PointOutcome::Set);
+        assert_eq!(board.result(), (1, 1));
+        assert_eq!(set(&mut board, Away), PointOutcome::Match);
+
+        assert_eq!(board.result(), (1, 2));
+        assert_eq!(board.get_sets(), [(6, 0), (0, 6), (0, 6)]);
+        assert_eq!(
+            text(&board),
+            ("6 0 0 | 0 | 0".to_owned(), "0 6 6 | 0 | 0".to_owned())
+        );
+    }
+
+    #[test]
+    fn short_matches_are_a_race_to_five() {
+        let mut board = Scoreboard::new(Scoring::Short);
+        assert_eq!(play(&mut board, &[Home; 4]), PointOutcome::Point);
+        assert_eq!(play(&mut board, &[Away; 4]), PointOutcome::Point);
+        assert_eq!(board.award_point(Home), PointOutcome::Point);
+        assert_eq!(text(&board), ("5".to_owned(), "4".to_owned()));
+        assert_eq!(board.next_server(Home), Away);
+
+        assert_eq!(board.award_point(Home), PointOutcome::Match);
+        assert_eq!(board.result(), (6, 4));
+    }
+}
@@ -1,45 +1,181 @@
-use rand::{
-    distributions::{Distribution, Standard},
-    Rng,
-};
+use std::str::FromStr;
+
+use rand::{seq::IteratorRandom, Rng};
+use serde::{Deserialize, Serialize};
 
 use crate::team::TeamId;
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
-pub enum Space {
-    First,
-    Middle,
-    Third,
+/// A spot on one side of the court. Lanes go across it, and depth goes from the net at 0 back
+/// to the baseline
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Space {
+    pub lane: usize,
+    pub depth: usize,
 }
 
 impl Space {
-    pub fn farthest<R: Rng>(self, rng: &mut R) -> Self {
-        match self {
-            Self::First => Self::Third,
-            Self::Middle => loop {
-                let x: Self = rng.gen();
-                if x != self {
-                    break x;
-                }
-            },
-            Self::Third => Self::First,
+    /// How many steps it takes to get from one space to the other, going diagonally counts as
+    /// one step
+    pub const fn distance(self, other: Self) -> usize {
+        let lanes = self.lane.abs_diff(other.lane);
+        let depths = self.depth.abs_diff(other.depth);
+        if lanes > depths {
+            lanes
+        } else {
+            depths
+        }
+    }
+
+    /// Whether a player in one of the spaces is right next to the other
+    pub const fn is_adjacent(self, other: Self) -> bool {
+        self.distance(other) == 1
+    }
+
+    /// The space one step closer to `target`
+    pub const fn toward(self, target: Self) -> Self {
+        const fn step(from: usize, to: usize) -> usize {
+            if from < to {
+                from + 1
+            } else if from > to {
+                from - 1
+            } else {
+                from
+            }
+        }
+        Self {
+            lane: step(self.lane, target.lane),
+            depth: step(self.depth, target.depth),
         }
     }
 }
 
-impl Distribution<Space> for Standard {
-    fn sample<R: rand::prelude::Rng + ?Sized>(&self, rng: &mut R) -> Space {
-        match rng.gen_range::<usize, _>(0..=2) {
-            0 => Space::First,
-            1 => Space::Middle,
-            2 => Space::Third,
-            _ => unreachable!(),
+/// A hit that goes way over or way short of the player who has to get it back
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Placement {
+    Normal,
+    /// Over the head of a player near the net
+    Lob,
+    /// Just over the net, in front of a player near the baseline
+    DropShot,
+}
+
+impl Placement {
+    /// What a ball going to `ball` looks like to a player waiting at `receiver`. Only courts
+    /// with some depth to them have room for lobs and drop shots
+    pub const fn new(receiver: Space, ball: Space) -> Self {
+        if ball.depth >= receiver.depth + 2 {
+            Self::Lob
+        } else if receiver.depth >= ball.depth + 2 {
+            Self::DropShot
+        } else {
+            Self::Normal
+        }
+    }
+}
+
+/// The shape of each side of the court
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Court {
+    lanes: usize,
+    depths: usize,
+}
+
+impl Court {
+    /// Three lanes and no depth at all, the way the game has always been played
+    pub const CLASSIC: Self = Self {
+        lanes: 3,
+        depths: 1,
+    };
+    /// Three lanes, each split into the net, mid-court and the baseline
+    pub const GRID: Self = Self {
+        lanes: 3,
+        depths: 3,
+    };
+    /// The most lanes or depths a court can have. A ball is in the air for two ticks at most and
+    /// players run two steps a tick at most, so from the middle of a court this size they can
+    /// still get to any space before the ball lands
+    const MAX_SIZE: usize = 9;
+
+    pub const fn get_lanes(self) -> usize {
+        self.lanes
+    }
+
+    /// The lane in the middle of the court, at the baseline
+    pub const fn center(self) -> Space {
+        Space {
+            lane: self.lanes / 2,
+            depth: self.baseline(),
+        }
+    }
+
+    pub const fn baseline(self) -> usize {
+        self.depths - 1
+    }
+
+    pub fn random<R: Rng>(self, rng: &mut R) -> Space {
+        Space {
+            lane: rng.gen_range(0..self.lanes),
+            depth: rng.gen_range(0..self.depths),
+        }
+    }
+
+    fn spaces(self) -> impl Iterator<Item = Space> {
+        (0..self.lanes)
+            .flat_map(move |lane| (0..self.depths).map(move |depth| Space { lane, depth }))
+    }
+
+    /// One of the spaces as far away from every player in `from` as they get, corners before
+    /// edges
+    pub fn farthest<R: Rng>(self, from: &[Space], rng: &mut R) -> Space {
+        let reach = |space: Space| {
+            from.iter()
+                .map(|player| {
+                    let lanes = space.lane.abs_diff(player.lane);
+                    let depths = space.depth.abs_diff(player.depth);
+                    lanes * lanes + depths * depths
+                })
+                .min()
+                .unwrap()
+        };
+        let most = self.spaces().map(reach).max().unwrap();
+        self.spaces()
+            .filter(|&space| reach(space) == most)
+            .choose(rng)
+            .unwrap()
+    }
+}
+
+impl FromStr for Court {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "classic" => Ok(Self::CLASSIC),
+            "grid" => Ok(Self::GRID),
+            _ => {
+                let (lanes, depths) = s
+                    .split_once('x')
+                    .and_then(|(lanes, depths)| Some((lanes.parse().ok()?, depths.parse().ok()?)))
+                    .ok_or_else(|| format!("unknown court: {s}"))?;
+                let sizes = 1..=Self::MAX_SIZE;
+                if !sizes.contains(&lanes) || !sizes.contains(&depths) {
+                    return Err(format!(
+                        "court {s} should have between 1 and {} lanes and depths",
+                        Self::MAX_SIZE
+                    ));
+                }
+                // With a single space the ball always lands right where the player is
+                if lanes * depths < 2 {
+                    return Err(format!("court {s} needs at least two spaces to miss in"));
+                }
+                Ok(Self { lanes, depths })
+            }
         }
     }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct PlayerState {
     pub(super) team: TeamId,
     pub(super) space: Space,
-    pub(super) score: usize,
 }
@@ -0,0 +1,163 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use crate::{career::Ledger, player::PlayerId, team::TeamId, Data};
+
+use super::{BoxScore, GameEvent, MatchRecord, Scoreboard, Side};
+
+/// How a finished game went, for the box score
+pub struct Summary {
+    pub home: TeamId,
+    pub away: TeamId,
+    pub scoreboard: Scoreboard,
+    pub winner: Side,
+    pub ticks: usize,
+    pub longest_rally: usize,
+    pub players: Ledger,
+    /// How many times each weather effect fired
+    pub weather_events: BTreeMap<&'static str, usize>,
+    pub roster_changes: Vec<GameEvent>,
+}
+
+impl Summary {
+    /// `incidents` should hold every weather effect and roster change in the game
+    pub fn new(
+        record: &MatchRecord,
+        scoreboard: &Scoreboard,
+        ticks: usize,
+        longest_rally: usize,
+        incidents: &[GameEvent],
+    ) -> Self {
+        let mut weather_events = BTreeMap::new();
+        let mut roster_changes = vec![];
+        for event in incidents {
+            if let Some(effect) = event.weather_effect() {
+                *weather_events.entry(effect).or_default() += 1;
+            }
+            if event.is_roster_change() {
+                roster_changes.push(event.clone());
+            }
+        }
+
+        Self {
+            home: record.home,
+            away: record.away,
+            scoreboard: scoreboard.clone(),
+            winner: record.winner,
+            ticks,
+            longest_rally,
+            players: record.stats.clone(),
+            weather_events,
+            roster_changes,
+        }
+    }
+
+    fn write_players(&self, output: &mut String, title: &str, players: &[PlayerId], data: &Data) {
+        writeln!(
+            output,
+            "{title:<30} {:>5} {:>5} {:>4} {:>4}",
+            "Hits", "Miss", "PW", "PL"
+        )
+        .unwrap();
+        for player in players {
+            let stats = self.players.get(player).unwrap();
+            let player = data.get_player(player).unwrap();
+            writeln!(
+                output,
+                "  {:<22} {} {:>5} {:>5} {:>4} {:>4}",
+                player.get_name(),
+                player.get_star_text(),
+                stats.hits,
+                stats.misses,
+                stats.points_won,
+                stats.points_lost,
+            )
+            .unwrap();
+        }
+    }
+}
+
+impl BoxScore for Summary {
+    fn get_text(&self, data: &Data) -> String {
+        let team_name = |id: &TeamId| data.get_team(id).unwrap().get_name();
+        let (home, away) = self.scoreboard.result();
+        let winner = match self.winner {
+            Side::Home => self.home,
+            Side::Away => self.away,
+        };
+
+        let mut output = String::new();
+        writeln!(output, "=========================").unwrap();
+        writeln!(
+            output,
+            "Final: {} {home} - {away} {}",
+            team_name(&self.home),
+            team_name(&self.away)
+        )
+        .unwrap();
+        if !self.scoreboard.get_sets().is_empty() {
+            let sets = self
+                .scoreboard
+                .get_sets()
+                .iter()
+                .map(|(home, away)| format!("{home}-{away}"))
+                .collect::<Vec<_>>()
+                .join(" ");
+            writeln!(output, "Sets: {sets}").unwrap();
+        }
+        writeln!(
+            output,
+            "{} win in {} ticks. Longest rally: {} hits",
+            team_name(&winner),
+            self.ticks,
+            self.longest_rally
+        )
+        .unwrap();
+
+        // Players are listed under the team they ended the game on, a swap can move them
+        let mut listed = vec![];
+        for team in [self.home, self.away] {
+            let players: Vec<_> = self
+                .players
+                .ranking()
+                .into_iter()
+                .filter(|x| data.get_team(&team).unwrap().get_players().contains(x))
+                .collect();
+            writeln!(output).unwrap();
+            self.write_players(&mut output, team_name(&team), &players, data);
+            listed.extend(players);
+        }
+        let gone: Vec<_> = self
+            .players
+            .ranking()
+            .into_iter()
+            .filter(|x| !listed.contains(x))
+            .collect();
+        if !gone.is_empty() {
+            writeln!(output).unwrap();
+            self.write_players(&mut output, "Gone", &gone, data);
+        }
+
+        writeln!(output).unwrap();
+        if self.weather_events.is_empty() {
+            writeln!(output, "Weather: nothing happened").unwrap();
+        } else {
+            let events = self
+                .weather_events
+                .iter()
+                .map(|(effect, count)| format!("{effect} x{count}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            writeln!(output, "Weather: {events}").unwrap();
+        }
+
+        if !self.roster_changes.is_empty() {
+            writeln!(output, "Roster changes:").unwrap();
+            for event in &self.roster_changes {
+                writeln!(output, "  {}", event.get_text(data)).unwrap();
+            }
+        }
+        writeln!(output, "=========================").unwrap();
+
+        output
+    }
+}
@@ -0,0 +1,206 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{career::Ledger, player::PlayerId, ratings::PointPlayed, team::TeamId, Data};
+
+use super::{
+    GameEvent, MatchRecord, PointOutcome, Scoreboard, Scoring, Side, Summary, LET_CHANCE,
+    MAX_INJURY_DAYS,
+};
+
+/// Keeps score and calls the serves, the same way whatever kind of game is being played. Also
+/// keeps track of everything the league needs once the game is over
+#[derive(Serialize, Deserialize)]
+pub struct Umpire {
+    /// The side that served this point
+    pub(super) server: Side,
+    pub(super) ticks: usize,
+    pub(super) scoreboard: Scoreboard,
+
+    /// Hits since the last serve, players tire as it grows
+    pub(super) rally: usize,
+    longest_rally: usize,
+    /// Whether the server already faulted once this point
+    second_serve: bool,
+
+    /// Who was on court for every point, so the players can be rated once the game is over
+    points: Vec<PointPlayed>,
+    /// What every player did in this game, added to their careers once it's over
+    stats: Ledger,
+    /// Weather effects and roster changes, for the box score
+    incidents: Vec<GameEvent>,
+}
+
+impl Umpire {
+    pub const fn new(scoring: Scoring) -> Self {
+        Self {
+            server: Side::Home,
+            ticks: 0,
+            scoreboard: Scoreboard::new(scoring),
+            rally: 0,
+            longest_rally: 0,
+            second_serve: false,
+            points: Vec::new(),
+            stats: Ledger::new(),
+            incidents: Vec::new(),
+        }
+    }
+
+    pub const fn get_longest_rally(&self) -> usize {
+        self.longest_rally
+    }
+
+    /// The side that's ahead, which after the last tick is the side that won
+    pub const fn get_winner(&self) -> Side {
+        let (home, away) = self.scoreboard.result();
+        if home > away {
+            Side::Home
+        } else {
+            Side::Away
+        }
+    }
+
+    pub fn get_record(&self, home: TeamId, away: TeamId) -> MatchRecord<'_> {
+        MatchRecord {
+            home,
+            away,
+            winner: self.get_winner(),
+            points: &self.points,
+            stats: &self.stats,
+        }
+    }
+
+    pub fn get_summary(&self, home: TeamId, away: TeamId) -> Summary {
+        Summary::new(
+            &self.get_record(home, away),
+            &self.scoreboard,
+            self.ticks,
+            self.longest_rally,
+            &self.incidents,
+        )
+    }
+
+    pub const fn new_point(&mut self, server: Side) {
+        self.server = server;
+        self.rally = 0;
+    }
+
+    /// Calls lets and faults on a serve by `player`. Returns what to report when the serve
+    /// doesn't go in, and the point is lost if that's a double fault
+    pub fn call_serve<R: Rng>(
+        &mut self,
+        player: PlayerId,
+        data: &Data,
+        rng: &mut R,
+    ) -> Option<GameEvent> {
+        if rng.gen::<f64>() < LET_CHANCE {
+            return Some(GameEvent::Let { player });
+        }
+
+        if data.get_player(&player).unwrap().fault_check(rng) {
+            self.second_serve = !self.second_serve;
+            return Some(if self.second_serve {
+                GameEvent::Fault { player }
+            } else {
+                GameEvent::DoubleFault { player }
+            });
+        }
+        self.second_serve = false;
+        None
+    }
+
+    /// Whether a serve by `server` that went in lands where they aimed it, and the ace to report
+    /// if it was also too hard for `receiver` to get to
+    pub fn call_ace<R: Rng>(
+        server: PlayerId,
+        receiver: PlayerId,
+        data: &Data,
+        rng: &mut R,
+    ) -> (bool, Option<GameEvent>) {
+        let (server_player, receiver_player) = (
+            data.get_player(&server).unwrap(),
+            data.get_player(&receiver).unwrap(),
+        );
+        let accurate = server_player.serve_accuracy_check(rng);
+        let ace =
+            accurate && server_player.power_check(rng) && !receiver_player.speed_check(0, rng);
+        let event = ace.then_some(GameEvent::Ace {
+            player: server,
+            receiver,
+        });
+        (accurate, event)
+    }
+
+    pub fn count_hit(&mut self) {
+        self.rally += 1;
+        self.longest_rally = self.longest_rally.max(self.rally);
+    }
+
+    /// Gives `side` the point that was played between the two players in `point`
+    pub fn award_point(&mut self, side: Side, point: PointPlayed) -> PointOutcome {
+        self.points.push(point);
+        self.scoreboard.award_point(side)
+    }
+
+    /// What to report when `team`, on `side`, takes a game or a set with the last point
+    pub fn announce(&self, outcome: PointOutcome, side: Side, team: TeamId) -> Option<GameEvent> {
+        match outcome {
+            PointOutcome::Set => {
+                let (games, other_games) = self.scoreboard.last_set(side).unwrap();
+                Some(GameEvent::SetWon {
+                    team,
+                    games,
+                    other_games,
+                })
+            }
+            PointOutcome::Game => Some(GameEvent::GameWon { team }),
+            PointOutcome::Point | PointOutcome::Match => None,
+        }
+    }
+
+    /// Rolls for `player` getting hurt after a point, more so when they're tired
+    pub fn check_injury<R: Rng>(
+        player: PlayerId,
+        data: &mut Data,
+        rng: &mut R,
+    ) -> Option<GameEvent> {
+        if !data.get_player(&player).unwrap().injury_check(rng) {
+            return None;
+        }
+        let days = rng.gen_range(1..=MAX_INJURY_DAYS);
+        data.get_player_mut(&player).unwrap().injure(days);
+        Some(GameEvent::Injury { player, days })
+    }
+
+    /// Sends the next healthy player in the lineup on for `player` if they're injured, skipping
+    /// whoever is in `on_court`. When there's nobody left, the injured player has to play on.
+    /// Returns who's playing, and what to report if anything changed
+    pub fn field(
+        team: TeamId,
+        player: PlayerId,
+        on_court: &[PlayerId],
+        data: &mut Data,
+    ) -> (PlayerId, Option<GameEvent>) {
+        if !data.get_player(&player).unwrap().is_injured() {
+            return (player, None);
+        }
+        team.next_healthy_player(on_court, data).map_or(
+            (player, Some(GameEvent::PlayingHurt { player, team })),
+            |substitute| {
+                let event = GameEvent::StepUp {
+                    player: substitute,
+                    team,
+                };
+                (substitute, Some(event))
+            },
+        )
+    }
+
+    /// Notes down what happened in the stats, and for the box score if it changed the match
+    pub fn record(&mut self, event: &GameEvent) {
+        self.stats.record_event(event);
+        if event.weather_effect().is_some() || event.is_roster_change() {
+            self.incidents.push(event.clone());
+        }
+    }
+}
@@ -1,174 +1,358 @@
-use crate::Team;
-use std::fmt::Display;
+use std::{collections::BTreeSet, fmt::Display, fs, path::Path, sync::Arc};
 
-use rand::{
-    distributions::{Distribution, Standard},
-    Rng,
-};
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
 
-use crate::Data;
+use crate::{player::PlayerId, team::TeamId, Data};
 
-use super::{Game, Run, Side};
+use super::{GameEvent, Run, Side};
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
-pub enum Weather {
-    None,
-    Feedback,
-    Reverb,
-    Observation,
-    Omni,
-    Unpredictable,
+/// One of the weathers the league knows about, by name
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Weather {
+    name: String,
+}
+
+impl Weather {
+    /// The label shown in the feed, which is just the name if the league doesn't know it
+    pub fn get_label<'a>(&'a self, climate: &'a Climate) -> &'a str {
+        climate.get(self).map_or(&self.name, |x| &x.label)
+    }
+
+    pub fn get_announcement(&self, climate: &Climate) -> String {
+        climate.get(self).map_or_else(
+            || format!("The weather turns to {}.", self.name),
+            |x| x.announcement.clone(),
+        )
+    }
 }
 
 impl Display for Weather {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::None => write!(f, "Clear"),
-            Self::Feedback => write!(f, "Feedback"),
-            Self::Reverb => write!(f, "Reverb"),
-            Self::Observation => write!(f, "Observation"),
-            Self::Unpredictable => write!(f, "???"),
-            Self::Omni => write!(f, "All"),
-        }
+        write!(f, "{}", self.name)
+    }
+}
+
+/// When in a game a trigger is rolled
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Phase {
+    /// Before the first serve of the match
+    MatchStart,
+    /// Right after a serve goes in
+    Serve,
+    /// Right before the hitter gets to the ball
+    PreHit,
+    /// Right after the hitter sends the ball back
+    PostHit,
+    /// Right after someone wins a point
+    Score,
+    /// Once the match is won, before it goes in the books
+    MatchEnd,
+}
+
+/// The built-in things weather can do, which definitions put together
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Effect {
+    /// The two players on court trade teams
+    Swap,
+    /// Both lineups get shuffled
+    Shuffle,
+    /// The player leaves the league and a new player takes their place
+    ReplacePlayer,
+    /// The player is watched, which does nothing at all
+    Watch,
+    /// The hitter doesn't get to hit the ball, only in the pre-hit phase
+    PreventHit,
+    /// Someone from the stands joins the player's team
+    Recruit,
+    /// A new weather rolls in and takes effect right away, without changing or mimicking again
+    ChangeWeather,
+    /// A random weather takes effect for this phase only, without changing or mimicking again
+    Mimic,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Trigger {
+    pub phase: Phase,
+    /// From 0 to 1, how likely the trigger is to go off every time its phase comes around
+    pub chance: f64,
+    pub effects: Vec<Effect>,
+}
+
+/// What a weather is called and what it does
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Definition {
+    pub name: String,
+    pub label: String,
+    /// What the announcer says when the weather rolls in
+    pub announcement: String,
+    /// How likely the weather is to be picked for a match, compared to the others
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+    /// Rolled in order, only the first one that goes off has any effect
+    #[serde(default)]
+    pub triggers: Vec<Trigger>,
+}
+
+const fn default_weight() -> f64 {
+    1.0
+}
+
+/// Every weather the league knows about
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Climate {
+    #[serde(rename = "weather")]
+    weathers: Vec<Definition>,
+}
+
+impl Default for Climate {
+    fn default() -> Self {
+        Self::from_toml(include_str!("../weathers.toml")).unwrap()
     }
 }
 
-impl Distribution<Weather> for Standard {
-    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Weather {
-        match rng.gen_range::<usize, _>(0..=4) {
-            0 => Weather::None,
-            1 => Weather::Feedback,
-            2 => Weather::Reverb,
-            3 => Weather::Observation,
-            4 => Weather::Unpredictable,
-            _ => unreachable!(),
+impl Climate {
+    /// Reads the weathers from a TOML file, see `weathers.toml` for how they're written
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text =
+            fs::read_to_string(path).map_err(|err| format!("couldn't read the weathers: {err}"))?;
+        Self::from_toml(&text)
+    }
+
+    fn from_toml(text: &str) -> Result<Self, String> {
+        let climate: Self =
+            toml::from_str(text).map_err(|err| format!("invalid weathers: {err}"))?;
+
+        let mut names = BTreeSet::new();
+        for weather in &climate.weathers {
+            if !names.insert(&weather.name) {
+                return Err(format!("weather {} is defined twice", weather.name));
+            }
+            if !weather.weight.is_finite() || weather.weight < 0.0 {
+                return Err(format!(
+                    "weather {} has a weight of {}, it needs to be a number of 0 or more",
+                    weather.name, weather.weight
+                ));
+            }
+            for trigger in &weather.triggers {
+                if !(0.0..=1.0).contains(&trigger.chance) {
+                    return Err(format!(
+                        "weather {} has a trigger with a chance of {}, which isn't between 0 and 1",
+                        weather.name, trigger.chance
+                    ));
+                }
+                if trigger.phase != Phase::PreHit && trigger.effects.contains(&Effect::PreventHit) {
+                    return Err(format!(
+                        "weather {} can only prevent hits in the pre-hit phase",
+                        weather.name
+                    ));
+                }
+            }
+        }
+        if !climate.weathers.iter().any(|x| x.weight > 0.0) {
+            return Err("at least one weather needs a weight above 0".to_owned());
+        }
+
+        Ok(climate)
+    }
+
+    pub fn get(&self, weather: &Weather) -> Option<&Definition> {
+        self.weathers.iter().find(|x| x.name == weather.name)
+    }
+
+    /// A weather for a match, weighted by how common each is
+    pub fn pick<R: Rng>(&self, rng: &mut R) -> Weather {
+        let weather = self.weathers.choose_weighted(rng, |x| x.weight).unwrap();
+        Weather {
+            name: weather.name.clone(),
         }
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub enum WeatherResult {
     Prevent,
     Nothing,
 }
 
-impl Weather {
-    fn weather_announce(self, hitter: Side, game: &mut Game, data: &Data) {
-        match self {
-            Self::None => game.report("It's a sunny day!".to_owned(), data),
-            Self::Feedback => {
-                game.report("The feedback gathers around the players.".to_owned(), data);
-            }
-            Self::Reverb => game.report("The ground tremors with reverb.".to_owned(), data),
-            Self::Observation => game.report("The clouds reveal eyes in the sky.".to_owned(), data),
-            Self::Omni => game.report("We're experiencing everything.".to_owned(), data),
-            Self::Unpredictable => {
-                game.report("We don't know what the sky is doing.".to_owned(), data);
-            }
-        }
+/// A kind of game weather can happen in. Effects fall on the player each side has in the thick
+/// of things, whoever that is for the kind of game
+pub trait Venue: Run<Event = GameEvent> {
+    fn get_team_id(&self, side: Side) -> TeamId;
+    fn get_current_player(&self, side: Side, data: &Data) -> PlayerId;
+    /// Puts `player` on court and in the lineup in place of the current player for `side`
+    fn replace_player(&mut self, side: Side, player: PlayerId, data: &mut Data);
+    fn set_weather(&mut self, weather: Weather);
+}
+
+/// What weather gets to do at each point of a match. Every hook can report events and change
+/// the game and the league however it likes
+pub trait Hooks {
+    fn match_start<G: Venue, R: Rng>(&self, game: &mut G, data: &mut Data, rng: &mut R);
+    fn serve<G: Venue, R: Rng>(&self, server: Side, game: &mut G, data: &mut Data, rng: &mut R);
+    /// The only hook that can keep the hitter from hitting
+    fn pre_hit<G: Venue, R: Rng>(
+        &self,
+        hitter: Side,
+        game: &mut G,
+        data: &mut Data,
+        rng: &mut R,
+    ) -> WeatherResult;
+    fn post_hit<G: Venue, R: Rng>(&self, hitter: Side, game: &mut G, data: &mut Data, rng: &mut R);
+    fn score<G: Venue, R: Rng>(&self, scorer: Side, game: &mut G, data: &mut Data, rng: &mut R);
+    fn match_end<G: Venue, R: Rng>(&self, winner: Side, game: &mut G, data: &mut Data, rng: &mut R);
+}
+
+/// Weather from the league's definitions rolls the triggers for each phase. Effects fall on the
+/// player the phase is about, which is the home player at the start of the match since they
+/// serve first
+impl Hooks for Weather {
+    fn match_start<G: Venue, R: Rng>(&self, game: &mut G, data: &mut Data, rng: &mut R) {
+        self.roll(Phase::MatchStart, Side::Home, false, game, data, rng);
     }
-    pub(super) fn pre_hit<R: Rng>(
-        self,
+
+    fn serve<G: Venue, R: Rng>(&self, server: Side, game: &mut G, data: &mut Data, rng: &mut R) {
+        self.roll(Phase::Serve, server, false, game, data, rng);
+    }
+
+    fn pre_hit<G: Venue, R: Rng>(
+        &self,
         hitter: Side,
-        game: &mut Game,
+        game: &mut G,
+        data: &mut Data,
+        rng: &mut R,
+    ) -> WeatherResult {
+        self.roll(Phase::PreHit, hitter, false, game, data, rng)
+    }
+
+    fn post_hit<G: Venue, R: Rng>(&self, hitter: Side, game: &mut G, data: &mut Data, rng: &mut R) {
+        self.roll(Phase::PostHit, hitter, false, game, data, rng);
+    }
+
+    fn score<G: Venue, R: Rng>(&self, scorer: Side, game: &mut G, data: &mut Data, rng: &mut R) {
+        self.roll(Phase::Score, scorer, false, game, data, rng);
+    }
+
+    fn match_end<G: Venue, R: Rng>(
+        &self,
+        winner: Side,
+        game: &mut G,
+        data: &mut Data,
+        rng: &mut R,
+    ) {
+        self.roll(Phase::MatchEnd, winner, false, game, data, rng);
+    }
+}
+
+impl Weather {
+    /// Rolls the triggers for `phase`. A `borrowed` weather is one that was just changed to or
+    /// mimicked, and it can't change or mimic again, so weathers can't keep picking each other
+    fn roll<G: Venue, R: Rng>(
+        &self,
+        phase: Phase,
+        side: Side,
+        borrowed: bool,
+        game: &mut G,
         data: &mut Data,
         rng: &mut R,
     ) -> WeatherResult {
-        let (hitter_state, _) = match hitter {
-            Side::Home => (&mut game.home, &mut game.away),
-            Side::Away => (&mut game.away, &mut game.home),
+        let climate = Arc::clone(data.get_climate());
+        let Some(definition) = climate.get(self) else {
+            return WeatherResult::Nothing;
         };
-        match self {
-            Self::None => WeatherResult::Nothing,
-            Self::Feedback => {
-                if rng.gen::<f64>() < 0.05 {
-                    let away_id = game.away.team.get_current_player(data).unwrap();
-                    let home_id = game.home.team.get_current_player(data).unwrap();
-                    *game.home.team.get_current_player_mut(data).unwrap() = away_id;
-                    *game.away.team.get_current_player_mut(data).unwrap() = home_id;
-                    game.report(
-                        format!(
-                            "{} has been feedbacked with {}!",
-                            data.get_player(&home_id).unwrap().get_name(),
-                            data.get_player(&away_id).unwrap().get_name()
-                        ),
-                        data,
-                    );
-                }
-                WeatherResult::Nothing
-            }
-            Self::Reverb => {
-                if rng.gen::<f64>() < 0.05 {
-                    data.get_team_mut(&game.home.team)
-                        .unwrap()
-                        .shuffle_players(rng);
-                    data.get_team_mut(&game.away.team)
-                        .unwrap()
-                        .shuffle_players(rng);
-
-                    game.report("The teams are caught in the reverb!!".to_string(), data);
-                }
 
-                WeatherResult::Nothing
+        let fired = definition
+            .triggers
+            .iter()
+            .filter(|x| x.phase == phase)
+            .find(|x| rng.gen::<f64>() < x.chance);
+
+        let mut result = WeatherResult::Nothing;
+        for &effect in fired.map_or(&[][..], |x| &x.effects) {
+            if borrowed && matches!(effect, Effect::ChangeWeather | Effect::Mimic) {
+                continue;
             }
-            Self::Observation => {
-                if rng.gen::<f64>() < 0.05 {
-                    let old_player = data
-                        .get_team(&hitter_state.team)
-                        .and_then(Team::get_current_player)
-                        .unwrap();
-
-                    let new_player = data.new_player(rng);
-                    *data
-                        .get_team_mut(&hitter_state.team)
-                        .and_then(|x| x.get_current_player_mut())
-                        .unwrap() = new_player;
-
-                    game.report(
-                        format!(
-                            "The observers have defragged {}.",
-                            data.get_player(&old_player).unwrap().get_name(),
-                        ),
-                        data,
-                    );
-
-                    game.report(
-                        format!(
-                            "{} has been created in their place! They don't know what's going on!",
-                            data.get_player(&new_player).unwrap().get_name(),
-                        ),
-                        data,
-                    );
-
-                    WeatherResult::Prevent
-                } else if rng.gen::<f64>() < 0.1 {
-                    let hitter = data
-                        .get_team(&hitter_state.team)
-                        .and_then(Team::get_current_player)
-                        .unwrap();
-
-                    game.report(
-                        format!(
-                            "The overseers watch {} with intent.",
-                            data.get_player(&hitter).unwrap().get_name(),
-                        ),
-                        data,
-                    );
-                    WeatherResult::Nothing
-                } else {
-                    WeatherResult::Nothing
-                }
+            if matches!(
+                apply(effect, phase, side, game, &climate, data, rng),
+                WeatherResult::Prevent
+            ) {
+                result = WeatherResult::Prevent;
             }
-            Self::Omni => rng.gen::<Self>().pre_hit(hitter, game, data, rng),
-            Self::Unpredictable => {
-                if rng.gen::<f64>() < 0.05 {
-                    game.weather = rng.gen();
-                    game.weather.weather_announce(hitter, game, data);
-                    game.weather.pre_hit(hitter, game, data, rng)
-                } else {
-                    WeatherResult::Nothing
-                }
+        }
+        result
+    }
+}
+
+fn apply<G: Venue, R: Rng>(
+    effect: Effect,
+    phase: Phase,
+    side: Side,
+    game: &mut G,
+    climate: &Climate,
+    data: &mut Data,
+    rng: &mut R,
+) -> WeatherResult {
+    let team = game.get_team_id(side);
+    match effect {
+        Effect::Swap => {
+            let away_id = game.get_current_player(Side::Away, data);
+            let home_id = game.get_current_player(Side::Home, data);
+            game.replace_player(Side::Home, away_id, data);
+            game.replace_player(Side::Away, home_id, data);
+            game.report(
+                GameEvent::Swap {
+                    home: home_id,
+                    away: away_id,
+                },
+                data,
+            );
+        }
+        Effect::Shuffle => {
+            for side in [Side::Home, Side::Away] {
+                data.get_team_mut(&game.get_team_id(side))
+                    .unwrap()
+                    .shuffle_players(rng);
             }
+
+            game.report(GameEvent::Shuffle, data);
+        }
+        Effect::ReplacePlayer => {
+            let old_player = game.get_current_player(side, data);
+            let new_player = data.new_player(rng);
+            game.replace_player(side, new_player, data);
+
+            game.report(GameEvent::Replaced { player: old_player }, data);
+            game.report(GameEvent::Replacement { player: new_player }, data);
+        }
+        Effect::Watch => {
+            let player = game.get_current_player(side, data);
+
+            game.report(GameEvent::Watch { player }, data);
+        }
+        Effect::PreventHit => return WeatherResult::Prevent,
+        Effect::Recruit => {
+            let player = data.new_player(rng);
+            data.get_team_mut(&team).unwrap().add_player(player);
+
+            game.report(GameEvent::Recruit { player, team }, data);
+        }
+        Effect::ChangeWeather => {
+            let weather = climate.pick(rng);
+            game.set_weather(weather.clone());
+            game.report(
+                GameEvent::WeatherChange {
+                    weather: weather.clone(),
+                },
+                data,
+            );
+            return weather.roll(phase, side, true, game, data, rng);
         }
+        Effect::Mimic => return climate.pick(rng).roll(phase, side, true, game, data, rng),
     }
+    WeatherResult::Nothing
 }
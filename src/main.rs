@@ -1,16 +1,29 @@
 #![warn(clippy::pedantic)]
 #![warn(clippy::nursery)]
 #![allow(clippy::module_name_repetitions)]
+mod career;
+mod cli;
 mod game;
 mod player;
+mod playoffs;
+mod ratings;
+mod save;
+mod season;
+mod simulate;
 mod team;
+mod tournament;
 
-use game::{Game, Result, Run};
+use career::Ledger;
+use cli::{Command, FeedFormat, Options, Playback};
+use game::{BoxScore, Climate, Doubles, Feed, Game, MatchRecord, Result, Run};
 use rand::{prelude::SliceRandom, Rng, SeedableRng};
 use rand_chacha::ChaCha20Rng;
-use std::{collections::HashMap, time::Duration};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt::Display, str::FromStr, sync::Arc};
 
 use player::{Player, PlayerId};
+use ratings::Ratings;
+use season::Season;
 use team::{Team, TeamId};
 
 struct NameGenerator {
@@ -18,6 +31,17 @@ struct NameGenerator {
     last_names: Vec<String>,
 }
 
+impl Default for NameGenerator {
+    fn default() -> Self {
+        let names = include_str!("names.txt");
+        let names = names.lines().map(ToOwned::to_owned).collect();
+        let last_names = include_str!("lastnames.txt");
+        let last_names = last_names.lines().map(ToOwned::to_owned).collect();
+
+        Self { names, last_names }
+    }
+}
+
 impl NameGenerator {
     fn generate<R: Rng>(&self, rng: &mut R) -> String {
         let name = self.names.choose(rng).unwrap();
@@ -25,79 +49,331 @@ impl NameGenerator {
 
         format!("{name} {last_name}")
     }
+
+    fn generate_team_name<R: Rng>(&self, rng: &mut R) -> String {
+        let name = self.last_names.choose(rng).unwrap();
+
+        if name.ends_with('s') {
+            format!("The {name}")
+        } else if let Some(stem) = name.strip_suffix('y') {
+            format!("The {stem}ies")
+        } else {
+            format!("The {name}s")
+        }
+    }
 }
 
+/// How much a player tires from playing a match, on top of every hit they make
+const MATCH_FATIGUE: f64 = 0.05;
+
+/// Cloning is cheap: the maps are shared until one of the clones changes them
+#[derive(Clone, Serialize, Deserialize)]
 struct Data {
-    teams: HashMap<TeamId, Team>,
-    players: HashMap<PlayerId, Player>,
-    name_generator: NameGenerator,
+    teams: Arc<BTreeMap<TeamId, Team>>,
+    players: Arc<BTreeMap<PlayerId, Player>>,
+    // The name lists are built into the binary, so they don't need to be saved
+    #[serde(skip)]
+    name_generator: Arc<NameGenerator>,
+    // Leagues saved before these were added start without them
+    #[serde(default)]
+    season: Option<Arc<Season>>,
+    #[serde(default)]
+    ratings: Arc<Ratings>,
+    #[serde(default)]
+    career_stats: Arc<Ledger>,
+    #[serde(default)]
+    climate: Arc<Climate>,
 }
 
 impl Data {
+    pub fn new() -> Self {
+        Self {
+            teams: Arc::default(),
+            players: Arc::default(),
+            name_generator: Arc::default(),
+            season: None,
+            ratings: Arc::default(),
+            career_stats: Arc::default(),
+            climate: Arc::default(),
+        }
+    }
+    pub fn find_team(&self, name: &str) -> Option<TeamId> {
+        self.teams
+            .iter()
+            .find(|(_, team)| team.get_name() == name)
+            .map(|(id, _)| *id)
+    }
+    pub fn find_player(&self, name: &str) -> Option<PlayerId> {
+        self.players
+            .iter()
+            .find(|(_, player)| player.get_name() == name)
+            .map(|(id, _)| *id)
+    }
+    pub fn get_team_ids(&self) -> Vec<TeamId> {
+        self.teams.keys().copied().collect()
+    }
     pub fn get_team(&self, id: &TeamId) -> Option<&Team> {
         self.teams.get(id)
     }
     pub fn get_team_mut(&mut self, id: &TeamId) -> Option<&mut Team> {
-        self.teams.get_mut(id)
+        Arc::make_mut(&mut self.teams).get_mut(id)
     }
     pub fn get_player(&self, id: &PlayerId) -> Option<&Player> {
         self.players.get(id)
     }
     pub fn get_player_mut(&mut self, id: &PlayerId) -> Option<&mut Player> {
-        self.players.get_mut(id)
+        Arc::make_mut(&mut self.players).get_mut(id)
+    }
+    pub fn get_ratings(&self) -> &Ratings {
+        &self.ratings
+    }
+    pub fn get_career_stats(&self) -> &Ledger {
+        &self.career_stats
+    }
+    /// Shared so the weather can look itself up while it changes the rest of the league
+    pub const fn get_climate(&self) -> &Arc<Climate> {
+        &self.climate
+    }
+    /// Updates the ratings and careers of everybody who took part in a finished game, who are
+    /// all a bit more tired for it
+    pub fn record_game(&mut self, record: &MatchRecord) {
+        Arc::make_mut(&mut self.ratings).record_game(record);
+        Arc::make_mut(&mut self.career_stats).record_game(record.stats);
+
+        for player in record.stats.ranking() {
+            if let Some(player) = self.get_player_mut(&player) {
+                player.tire(MATCH_FATIGUE);
+            }
+        }
+    }
+    /// Lets every player rest for a day
+    pub fn pass_day(&mut self) {
+        Arc::make_mut(&mut self.players)
+            .values_mut()
+            .for_each(Player::rest);
     }
 
     pub fn new_player<R: Rng>(&mut self, rng: &mut R) -> PlayerId {
         let (id, player) = Player::new(&self.name_generator, rng);
 
-        self.players.insert(id, player);
+        Arc::make_mut(&mut self.players).insert(id, player);
 
         id
     }
 
+    /// A name no other team in the league has
+    pub fn new_team_name<R: Rng>(&self, rng: &mut R) -> String {
+        loop {
+            let name = self.name_generator.generate_team_name(rng);
+            if self.find_team(&name).is_none() {
+                break name;
+            }
+        }
+    }
+
+    /// Every team in the league, plus as many new teams as needed to have at least `count`
+    pub fn fill_teams<R: Rng>(&mut self, count: usize, rng: &mut R) -> Vec<TeamId> {
+        let mut teams = self.get_team_ids();
+        while teams.len() < count {
+            let name = self.new_team_name(rng);
+            teams.push(self.add_team(name, rng));
+        }
+        teams
+    }
+
     pub fn add_team<R: Rng>(&mut self, name: String, rng: &mut R) -> TeamId {
-        let team_key = TeamId::new();
+        let team_key = TeamId::new(rng);
         let new_team = Team::random_team(name, self, rng);
-        self.teams.insert(team_key, new_team);
+        Arc::make_mut(&mut self.teams).insert(team_key, new_team);
 
         team_key
     }
 }
 
 fn main() {
-    let mut rng = ChaCha20Rng::from_entropy();
-    let names = include_str!("names.txt");
-    let names = names.lines().map(ToOwned::to_owned).collect();
-    let last_names = include_str!("lastnames.txt");
-    let last_names = last_names.lines().map(ToOwned::to_owned).collect();
-
-    let name_generator = NameGenerator { names, last_names };
-
-    let mut data = Data {
-        teams: HashMap::new(),
-        players: HashMap::new(),
-        name_generator,
-    };
+    let options = Options::from_args().unwrap_or_else(|err| {
+        eprintln!("{err}");
+        eprintln!("{}", cli::USAGE);
+        std::process::exit(2);
+    });
 
-    let home = data.add_team("The Speedles".to_owned(), &mut rng);
-    let away = data.add_team("The Spabbles".to_owned(), &mut rng);
+    match options.command {
+        Command::Play { kind } => (kind.play)(&options),
+        Command::Simulate { matches, threads } => simulate::run(&options, matches, threads),
+        Command::Season { teams, days } => season::run(&options, teams, days),
+        Command::Playoffs {
+            bracket,
+            best_of,
+            teams,
+        } => playoffs::run(&options, bracket, best_of, teams),
+        Command::Tournament {
+            system,
+            rounds,
+            teams,
+        } => tournament::run(&options, system, rounds, teams),
+        Command::Ratings => ratings::run(&options),
+        Command::Stats { ref player } => career::run(&options, player.as_deref()),
+    }
+}
+
+/// A kind of game that can be picked by name from the command line
+pub struct Kind {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Whether players take turns on court, so a rotation policy means something
+    pub rotation: bool,
+    /// Plays a match of this kind, as the command line asked for it
+    pub play: fn(&Options),
+}
+
+/// Every kind of game there is, the first one is played when none is picked
+pub const KINDS: &[Kind] = &[
+    Kind {
+        name: "singles",
+        description: "one player a side, taking turns through the lineup",
+        rotation: true,
+        play: play::<Game>,
+    },
+    Kind {
+        name: "doubles",
+        description: "two players a side, covering for each other",
+        rotation: false,
+        play: play::<Doubles>,
+    },
+];
+
+impl FromStr for &'static Kind {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        KINDS.iter().find(|x| x.name == s).ok_or_else(|| {
+            let kinds: Vec<_> = KINDS
+                .iter()
+                .map(|x| format!("{}, {}", x.name, x.description))
+                .collect();
+            format!("unknown game kind: {s} (expected {})", kinds.join("; "))
+        })
+    }
+}
 
-    let (mut game, mut result) = (Game::new(home, away, rng.gen()), Result::Continue);
+fn play<G: Run>(options: &Options) {
+    let (mut data, mut game, mut rng) = match &options.checkpoint {
+        Some(path) if path.exists() => {
+            let checkpoint = save::load_checkpoint::<G>(path).unwrap_or_else(|err| exit_with(err));
+            (checkpoint.data, checkpoint.game, checkpoint.rng)
+        }
+        _ => new_game(options),
+    };
 
-    println!("{rng:#?}");
+    let mut result = Result::Continue;
+    let mut ticks = 0;
+    let mut last_report = None;
 
     loop {
         if matches!(result, Result::Finished) {
             break;
         }
 
+        if options.stop_after.is_some_and(|x| ticks >= x) {
+            // Leave the checkpoint in place so the match can be resumed later
+            return;
+        }
+
         result = game.tick(&mut data, &mut rng);
+        ticks += 1;
 
         while let Some(report) = game.pop_report() {
-            println!("{}", report.get_text(&data));
-            std::thread::sleep(Duration::from_millis(
-                (100 + 100 * report.comment.len()).try_into().unwrap(),
-            ));
+            if matches!(options.playback, Playback::Live(_) | Playback::Instant) {
+                print_report(&report, &data, options.format);
+            }
+            if let Some(delay) = options.playback.delay(report.get_comment()) {
+                std::thread::sleep(delay);
+            }
+            last_report = Some(report);
+        }
+
+        if let Some(path) = &options.checkpoint {
+            save::save_checkpoint(path, &data, &game, &rng).unwrap_or_else(|err| exit_with(err));
         }
     }
+
+    if let Some(path) = &options.checkpoint {
+        std::fs::remove_file(path).unwrap();
+    }
+
+    if options.playback == Playback::Headless {
+        if let Some(report) = &last_report {
+            print_report(report, &data, options.format);
+        }
+    }
+
+    // The box score would break up the JSON Lines feed
+    if options.playback != Playback::Quiet && options.format == FeedFormat::Text {
+        print!("{}", game.get_summary().get_text(&data));
+    }
+
+    save_league(options, &data);
+}
+
+/// Stops over something that has to be fixed before anything can be played, like a broken save
+fn exit_with(error: impl Display) -> ! {
+    eprintln!("{error}");
+    std::process::exit(1);
+}
+
+fn print_report(report: &impl Feed, data: &Data, format: FeedFormat) {
+    match format {
+        FeedFormat::Text => println!("{}", report.get_text(data)),
+        FeedFormat::Jsonl => println!("{}", report.get_json(data)),
+    }
+}
+
+/// Loads the league, or starts a new one if there's nothing to load
+fn load_league(options: &Options) -> Data {
+    let mut data = match &options.league {
+        Some(path) if path.exists() => save::load(path).unwrap_or_else(|err| exit_with(err)),
+        _ => Data::new(),
+    };
+    if let Some(path) = &options.weathers {
+        data.climate = Arc::new(Climate::load(path).unwrap_or_else(|err| exit_with(err)));
+    }
+    data
+}
+
+fn save_league(options: &Options, data: &Data) {
+    if let Some(path) = &options.league {
+        save::save(path, data).unwrap_or_else(|err| exit_with(err));
+    }
+}
+
+/// Loads the league and finds the two teams that are going to play
+fn setup<R: Rng>(options: &Options, rng: &mut R) -> (Data, TeamId, TeamId) {
+    let mut data = load_league(options);
+
+    let home = data
+        .find_team(&options.home)
+        .unwrap_or_else(|| data.add_team(options.home.clone(), rng));
+    let away = data
+        .find_team(&options.away)
+        .unwrap_or_else(|| data.add_team(options.away.clone(), rng));
+
+    (data, home, away)
+}
+
+fn new_game<G: Run>(options: &Options) -> (Data, G, ChaCha20Rng) {
+    let seed = options.seed.unwrap_or_else(rand::random);
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
+
+    let (mut data, home, away) = setup(options, &mut rng);
+
+    let game = G::start(home, away, options.get_match_config(), &mut data, &mut rng);
+
+    // Keep the JSON Lines feed and headless output clean so they can be piped straight into other tools
+    match (options.playback, options.format) {
+        (Playback::Quiet, _) => (),
+        (Playback::Live(_) | Playback::Instant, FeedFormat::Text) => println!("Seed: {seed}"),
+        _ => eprintln!("Seed: {seed}"),
+    }
+
+    (data, game, rng)
 }
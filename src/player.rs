@@ -1,18 +1,53 @@
 use rand::Rng;
-use uuid::Uuid;
+use serde::{Deserialize, Serialize};
+use uuid::{Builder, Uuid};
 
 use crate::NameGenerator;
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct PlayerId {
     uuid: Uuid,
 }
 
+/// How worn out the player is. Unlike their attributes, this changes from day to day
+#[derive(Clone, Copy, Default, Debug, Serialize, Deserialize)]
+pub struct Condition {
+    /// From 0 to 1, builds up over rallies and matches and goes down with rest
+    pub fatigue: f64,
+    /// Days left until the player can play again
+    pub injured_days: usize,
+}
+
+/// Control and speed work backwards: the lower they are, the better the player.
+/// Every other attribute is better the higher it is, except distractability
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Player {
     name: String,
     control: f64,
     speed: f64,
     distractability: f64,
+    // Players saved before the rest of the attributes were added are average at them
+    /// How well the player keeps up as a rally drags on
+    #[serde(default = "average")]
+    stamina: f64,
+    /// How often the player hits a shot too fast to stretch for
+    #[serde(default = "average")]
+    power: f64,
+    /// How well the player keeps their focus as a rally drags on
+    #[serde(default = "average")]
+    composure: f64,
+    #[serde(default = "average")]
+    serve_accuracy: f64,
+    /// How often the player can get to a ball in the space next to them
+    #[serde(default = "average")]
+    reach: f64,
+    #[serde(default)]
+    condition: Condition,
+}
+
+const fn average() -> f64 {
+    0.5
 }
 
 impl Player {
@@ -22,7 +57,7 @@ impl Player {
 
     pub(super) fn new<R: Rng>(name_generator: &NameGenerator, rng: &mut R) -> (PlayerId, Self) {
         let new_id = PlayerId {
-            uuid: Uuid::new_v4(),
+            uuid: Builder::from_random_bytes(rng.gen()).into_uuid(),
         };
 
         let new_player = Self {
@@ -30,22 +65,125 @@ impl Player {
             speed: rng.gen(),
             control: rng.gen(),
             distractability: rng.gen::<f64>().powi(2),
+            stamina: rng.gen(),
+            power: rng.gen(),
+            composure: rng.gen(),
+            serve_accuracy: rng.gen(),
+            reach: rng.gen(),
+            condition: Condition::default(),
         };
 
         (new_id, new_player)
     }
 
-    /// Successful if player is not distracted
-    pub fn distraction_check<R: Rng>(&self, rng: &mut R) -> bool {
-        rng.gen::<f64>() < self.distractability
+    pub const fn get_condition(&self) -> Condition {
+        self.condition
+    }
+
+    pub const fn is_injured(&self) -> bool {
+        self.condition.injured_days > 0
+    }
+
+    /// Wears the player out, less so the more stamina they have
+    pub fn tire(&mut self, amount: f64) {
+        self.condition.fatigue = amount
+            .mul_add(1.5 - self.stamina, self.condition.fatigue)
+            .min(1.0);
+    }
+
+    /// Keeps the player out for the next `days` days. The day they got hurt on counts too,
+    /// since it still has to go by before those start
+    pub const fn injure(&mut self, days: usize) {
+        self.condition.injured_days = days + 1;
+    }
+
+    /// A day goes by: some of the fatigue wears off and injuries heal a little
+    pub fn rest(&mut self) {
+        self.condition.fatigue *= 0.7;
+        self.condition.injured_days = self.condition.injured_days.saturating_sub(1);
+    }
+
+    /// From 1 to 5, how good the player is overall
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn get_stars(&self) -> usize {
+        let overall = ((1.0 - self.control)
+            + (1.0 - self.speed)
+            + (1.0 - self.distractability)
+            + self.stamina
+            + self.power
+            + self.composure
+            + self.serve_accuracy
+            + self.reach)
+            / 8.0;
+
+        (overall * 5.0).round().clamp(1.0, 5.0) as usize
+    }
+
+    /// The star rating as it's shown next to the player's name
+    pub fn get_star_text(&self) -> String {
+        let stars = self.get_stars();
+        format!("{}{}", "★".repeat(stars), "☆".repeat(5 - stars))
+    }
+
+    /// Successful if player is not distracted, which gets harder as the rally goes on unless
+    /// the player keeps their composure
+    #[allow(clippy::cast_precision_loss)]
+    pub fn distraction_check<R: Rng>(&self, rally: usize, rng: &mut R) -> bool {
+        let pressure = 0.02 * rally as f64 * (1.0 - self.composure);
+        rng.gen::<f64>() < self.distractability + pressure
+    }
+
+    /// How much harder everything gets after `rally` hits without a break, on top of how tired
+    /// the player already was
+    #[allow(clippy::cast_precision_loss)]
+    fn tiredness(&self, rally: usize) -> f64 {
+        (0.03 * rally as f64).mul_add(1.0 - self.stamina, 0.2 * self.condition.fatigue)
     }
 
-    /// Successful if player is fast enough
-    pub fn speed_check<R: Rng>(&self, rng: &mut R) -> bool {
-        rng.gen::<f64>() > self.speed
+    /// Successful if player is fast enough, which gets harder as the rally goes on
+    pub fn speed_check<R: Rng>(&self, rally: usize, rng: &mut R) -> bool {
+        rng.gen::<f64>() > self.speed + self.tiredness(rally)
+    }
+    /// How many steps the player covers in a tick, two if they're fast enough
+    pub fn steps<R: Rng>(&self, rally: usize, rng: &mut R) -> usize {
+        if self.speed_check(rally, rng) {
+            2
+        } else {
+            1
+        }
+    }
+    /// How many ticks a ball the player hits takes to land. The harder they hit, the less time
+    /// there is to run for it
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn flight_time(&self, power_shot: bool) -> usize {
+        if power_shot {
+            1
+        } else {
+            1 + (1.0 - self.power).round() as usize
+        }
+    }
+    /// Successful if player has control, which gets harder as the rally goes on
+    pub fn control_check<R: Rng>(&self, rally: usize, rng: &mut R) -> bool {
+        rng.gen::<f64>() > self.control + self.tiredness(rally)
+    }
+    /// Successful if the shot is too fast for the other player to stretch for
+    pub fn power_check<R: Rng>(&self, rng: &mut R) -> bool {
+        rng.gen::<f64>() < self.power / 2.0
+    }
+    /// Successful if the player gets to a ball in the space next to them
+    pub fn reach_check<R: Rng>(&self, rally: usize, rng: &mut R) -> bool {
+        rng.gen::<f64>() + self.tiredness(rally) < self.reach / 2.0
+    }
+    /// Successful if the player gets hurt, which is likelier the more tired they are
+    pub fn injury_check<R: Rng>(&self, rng: &mut R) -> bool {
+        rng.gen::<f64>() < 0.004f64.mul_add(self.condition.fatigue, 0.001)
+    }
+    /// Successful if the serve goes out
+    pub fn fault_check<R: Rng>(&self, rng: &mut R) -> bool {
+        rng.gen::<f64>() < (1.0 - self.serve_accuracy) / 5.0
     }
-    /// Successful if player has control
-    pub fn control_check<R: Rng>(&self, rng: &mut R) -> bool {
-        rng.gen::<f64>() > self.control
+    /// Successful if the serve lands away from the receiver
+    pub fn serve_accuracy_check<R: Rng>(&self, rng: &mut R) -> bool {
+        rng.gen::<f64>() < self.serve_accuracy
     }
 }
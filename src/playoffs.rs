@@ -0,0 +1,350 @@
+use std::{collections::BTreeMap, fmt::Write, str::FromStr};
+
+use crate::{
+    cli::Options,
+    game::{Court, Rotation, Scoring},
+    season::{play_match, stream, MatchResult},
+    team::TeamId,
+    Data,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Bracket {
+    /// Losing a series knocks a team out
+    Single,
+    /// Losing a series drops a team to the losers bracket, losing there knocks it out
+    Double,
+}
+
+impl FromStr for Bracket {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "single" => Ok(Self::Single),
+            "double" => Ok(Self::Double),
+            _ => Err(format!("unknown bracket: {s}")),
+        }
+    }
+}
+
+/// A best-of-N matchup between two teams
+pub struct Series {
+    pub winner: TeamId,
+    pub loser: TeamId,
+    pub wins: usize,
+    pub losses: usize,
+    pub games: Vec<MatchResult>,
+}
+
+pub struct Playoffs {
+    bracket: Bracket,
+    best_of: usize,
+    scoring: Scoring,
+    rotation: Rotation,
+    court: Court,
+    seed: u64,
+    games_played: u64,
+    /// Each team's seed, 1 being the best
+    seeds: BTreeMap<TeamId, usize>,
+    round: usize,
+    winners: Vec<TeamId>,
+    losers: Vec<TeamId>,
+    eliminated: Vec<TeamId>,
+    /// Whether the grand final went to a deciding rematch
+    reset: bool,
+    champion: Option<TeamId>,
+}
+
+impl Playoffs {
+    /// `teams` should be ordered from the best seed to the worst
+    pub fn new(
+        teams: &[TeamId],
+        bracket: Bracket,
+        best_of: usize,
+        scoring: Scoring,
+        rotation: Rotation,
+        court: Court,
+        seed: u64,
+    ) -> Self {
+        Self {
+            bracket,
+            best_of,
+            scoring,
+            rotation,
+            court,
+            seed,
+            games_played: 0,
+            seeds: teams
+                .iter()
+                .enumerate()
+                .map(|(index, &team)| (team, index + 1))
+                .collect(),
+            round: 0,
+            winners: teams.to_vec(),
+            losers: vec![],
+            eliminated: vec![],
+            reset: false,
+            champion: teams.first().filter(|_| teams.len() == 1).copied(),
+        }
+    }
+
+    pub const fn is_finished(&self) -> bool {
+        self.champion.is_some()
+    }
+
+    pub const fn get_round(&self) -> usize {
+        self.round
+    }
+
+    /// Pairs the best seed left with the worst, the second best with the second worst, and so
+    /// on. With an odd number of teams the best seed gets a bye
+    fn pairings(&self, teams: &[TeamId]) -> (Vec<(TeamId, TeamId)>, Option<TeamId>) {
+        let mut teams = teams.to_vec();
+        teams.sort_by_key(|x| self.seeds[x]);
+
+        let bye = (teams.len() % 2 == 1).then(|| teams.remove(0));
+        let pairs = (0..teams.len() / 2)
+            .map(|index| (teams[index], teams[teams.len() - 1 - index]))
+            .collect();
+
+        (pairs, bye)
+    }
+
+    fn play_series(&mut self, high: TeamId, low: TeamId, data: &mut Data) -> Series {
+        let needed = self.best_of / 2 + 1;
+        let (mut high_wins, mut low_wins) = (0, 0);
+        let mut games = vec![];
+
+        while high_wins < needed && low_wins < needed {
+            let mut rng = stream(self.seed, self.games_played);
+            self.games_played += 1;
+
+            // The better seed hosts the first game, then the teams take turns
+            let (home, away) = if games.len() % 2 == 0 {
+                (high, low)
+            } else {
+                (low, high)
+            };
+            let result = play_match(
+                home,
+                away,
+                self.scoring,
+                self.rotation,
+                self.court,
+                data,
+                &mut rng,
+            );
+
+            if result.get_winner() == high {
+                high_wins += 1;
+            } else {
+                low_wins += 1;
+            }
+            games.push(result);
+        }
+
+        if high_wins > low_wins {
+            Series {
+                winner: high,
+                loser: low,
+                wins: high_wins,
+                losses: low_wins,
+                games,
+            }
+        } else {
+            Series {
+                winner: low,
+                loser: high,
+                wins: low_wins,
+                losses: high_wins,
+                games,
+            }
+        }
+    }
+
+    fn play_pairs(&mut self, pairs: Vec<(TeamId, TeamId)>, data: &mut Data) -> Vec<Series> {
+        pairs
+            .into_iter()
+            .map(|(high, low)| self.play_series(high, low, data))
+            .collect()
+    }
+
+    /// Plays every series of the next round
+    pub fn play_round(&mut self, data: &mut Data) -> Vec<Series> {
+        if self.is_finished() {
+            return vec![];
+        }
+        self.round += 1;
+
+        let played = match self.bracket {
+            Bracket::Single => {
+                let (pairs, bye) = self.pairings(&self.winners);
+                let played = self.play_pairs(pairs, data);
+
+                self.winners = bye
+                    .into_iter()
+                    .chain(played.iter().map(|x| x.winner))
+                    .collect();
+                self.eliminated.extend(played.iter().map(|x| x.loser));
+                played
+            }
+            Bracket::Double if self.winners.len() == 1 && self.losers.len() == 1 => {
+                let (winners_champion, losers_champion) = (self.winners[0], self.losers[0]);
+                let series = self.play_series(winners_champion, losers_champion, data);
+
+                if series.winner == losers_champion && !self.reset {
+                    // Both teams have lost once now, so they play again
+                    self.reset = true;
+                    self.winners = vec![series.winner];
+                    self.losers = vec![series.loser];
+                } else {
+                    self.winners = vec![series.winner];
+                    self.losers.clear();
+                    self.eliminated.push(series.loser);
+                }
+                vec![series]
+            }
+            Bracket::Double => {
+                let (winner_pairs, winner_bye) = if self.winners.len() > 1 {
+                    self.pairings(&self.winners)
+                } else {
+                    (vec![], self.winners.first().copied())
+                };
+                let (loser_pairs, loser_bye) = self.pairings(&self.losers);
+
+                let mut played = self.play_pairs(winner_pairs, data);
+                let winners_played = played.len();
+                played.extend(self.play_pairs(loser_pairs, data));
+
+                let (winners_round, losers_round) = played.split_at(winners_played);
+
+                self.winners = winner_bye
+                    .into_iter()
+                    .chain(winners_round.iter().map(|x| x.winner))
+                    .collect();
+                self.losers = loser_bye
+                    .into_iter()
+                    .chain(losers_round.iter().map(|x| x.winner))
+                    .chain(winners_round.iter().map(|x| x.loser))
+                    .collect();
+                self.eliminated.extend(losers_round.iter().map(|x| x.loser));
+                played
+            }
+        };
+
+        if self.winners.len() == 1 && self.losers.is_empty() {
+            self.champion = Some(self.winners[0]);
+        }
+        data.pass_day();
+        played
+    }
+
+    fn get_team_text(&self, team: &TeamId, data: &Data) -> String {
+        format!(
+            "({}) {}",
+            self.seeds[team],
+            data.get_team(team).unwrap().get_name()
+        )
+    }
+
+    pub fn get_series_text(&self, series: &Series, data: &Data) -> String {
+        let mut output = String::new();
+        writeln!(
+            output,
+            "  {} beat {} {}-{}",
+            self.get_team_text(&series.winner, data),
+            self.get_team_text(&series.loser, data),
+            series.wins,
+            series.losses
+        )
+        .unwrap();
+
+        if self.best_of > 1 {
+            for game in &series.games {
+                writeln!(output, "      {}", game.get_text(data)).unwrap();
+            }
+        }
+
+        output
+    }
+
+    /// Where every team stands in the bracket
+    pub fn get_text(&self, data: &Data) -> String {
+        let list = |teams: &[TeamId]| {
+            let mut teams = teams.to_vec();
+            teams.sort_by_key(|x| self.seeds[x]);
+            teams
+                .iter()
+                .map(|x| self.get_team_text(x, data))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        let mut output = String::new();
+        if let Some(champion) = &self.champion {
+            writeln!(output, "Champions: {}", self.get_team_text(champion, data)).unwrap();
+        } else {
+            match self.bracket {
+                Bracket::Single => {
+                    writeln!(output, "Still in: {}", list(&self.winners)).unwrap();
+                }
+                Bracket::Double => {
+                    writeln!(output, "Winners bracket: {}", list(&self.winners)).unwrap();
+                    if !self.losers.is_empty() {
+                        writeln!(output, "Losers bracket: {}", list(&self.losers)).unwrap();
+                    }
+                }
+            }
+        }
+        if !self.eliminated.is_empty() {
+            writeln!(output, "Eliminated: {}", list(&self.eliminated)).unwrap();
+        }
+
+        output
+    }
+}
+
+pub fn run(options: &Options, bracket: Bracket, best_of: usize, team_count: Option<usize>) {
+    let mut data = crate::load_league(options);
+
+    let Some(season) = &data.season else {
+        println!("There's no season to seed the playoffs from.");
+        return;
+    };
+
+    let mut teams = season.get_standings().ranking();
+    teams.truncate(team_count.unwrap_or(teams.len()));
+    if teams.is_empty() {
+        println!("There are no teams in the standings to seed the playoffs with.");
+        return;
+    }
+
+    let seed = options.seed.unwrap_or_else(rand::random);
+    println!("Seed: {seed}");
+
+    let mut playoffs = Playoffs::new(
+        &teams,
+        bracket,
+        best_of,
+        options.scoring,
+        options.rotation,
+        options.court,
+        seed,
+    );
+    print!("{}", playoffs.get_text(&data));
+
+    while !playoffs.is_finished() {
+        let played = playoffs.play_round(&mut data);
+
+        println!();
+        println!("Round {}", playoffs.get_round());
+        for series in &played {
+            print!("{}", playoffs.get_series_text(series, &data));
+        }
+        println!();
+        print!("{}", playoffs.get_text(&data));
+    }
+
+    crate::save_league(options, &data);
+}
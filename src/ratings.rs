@@ -0,0 +1,206 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cli::Options,
+    game::{MatchRecord, Side},
+    player::PlayerId,
+    team::TeamId,
+    Data,
+};
+
+/// What everybody starts at
+pub const INITIAL_RATING: f64 = 1500.0;
+/// How far a single match can move a team's rating
+const TEAM_K: f64 = 32.0;
+/// How far a single point can move a player's rating
+const PLAYER_K: f64 = 4.0;
+
+/// The chance, according to Elo, that something rated `rating` beats something rated `other`
+fn expected(rating: f64, other: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((other - rating) / 400.0))
+}
+
+/// A point as it was played, with the players who were on court for each team at the time
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct PointPlayed {
+    pub winner: PlayerId,
+    pub winner_team: TeamId,
+    pub loser: PlayerId,
+    pub loser_team: TeamId,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Rating {
+    pub rating: f64,
+    /// The number of each rated match this took part in, and the rating after it
+    pub history: Vec<(usize, f64)>,
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Self {
+            rating: INITIAL_RATING,
+            history: vec![],
+        }
+    }
+}
+
+impl Rating {
+    fn update(&mut self, game: usize, change: f64) {
+        self.rating += change;
+        self.history.push((game, self.rating));
+    }
+
+    /// How much the last rated match changed the rating
+    pub fn get_change(&self) -> f64 {
+        match self.history.as_slice() {
+            [.., (_, before), (_, after)] => after - before,
+            [(_, after)] => after - INITIAL_RATING,
+            [] => 0.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PlayerRating {
+    pub rating: Rating,
+    /// Points won and lost while playing for each team, since weather can swap players around
+    pub points: BTreeMap<TeamId, (usize, usize)>,
+}
+
+/// Elo ratings for every team and player that has played a match in the league
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Ratings {
+    games: usize,
+    teams: BTreeMap<TeamId, Rating>,
+    players: BTreeMap<PlayerId, PlayerRating>,
+}
+
+impl Ratings {
+    pub fn get_team(&self, id: &TeamId) -> f64 {
+        self.teams.get(id).map_or(INITIAL_RATING, |x| x.rating)
+    }
+
+    pub fn get_player(&self, id: &PlayerId) -> f64 {
+        self.players
+            .get(id)
+            .map_or(INITIAL_RATING, |x| x.rating.rating)
+    }
+
+    /// Rates a finished game. Teams are rated on who won the match and players on each point
+    /// they played, against whoever was on court for the other team
+    pub fn record_game(&mut self, game: &MatchRecord) {
+        self.games += 1;
+
+        let (home, away) = (game.home, game.away);
+        let home_score = if game.winner == Side::Home { 1.0 } else { 0.0 };
+        let change = TEAM_K * (home_score - expected(self.get_team(&home), self.get_team(&away)));
+
+        self.teams
+            .entry(home)
+            .or_default()
+            .update(self.games, change);
+        self.teams
+            .entry(away)
+            .or_default()
+            .update(self.games, -change);
+
+        // Every point is judged by the ratings from before the match, so the order they were
+        // played in doesn't matter
+        let mut changes: BTreeMap<PlayerId, f64> = BTreeMap::new();
+        for point in game.points {
+            let change = PLAYER_K
+                * (1.0
+                    - expected(
+                        self.get_player(&point.winner),
+                        self.get_player(&point.loser),
+                    ));
+            *changes.entry(point.winner).or_default() += change;
+            *changes.entry(point.loser).or_default() -= change;
+
+            let winner = self.players.entry(point.winner).or_default();
+            winner.points.entry(point.winner_team).or_default().0 += 1;
+            let loser = self.players.entry(point.loser).or_default();
+            loser.points.entry(point.loser_team).or_default().1 += 1;
+        }
+
+        for (player, change) in changes {
+            self.players
+                .entry(player)
+                .or_default()
+                .rating
+                .update(self.games, change);
+        }
+    }
+
+    pub fn get_text(&self, data: &Data) -> String {
+        let mut output = String::new();
+
+        let mut teams: Vec<_> = self.teams.iter().collect();
+        teams.sort_by(|a, b| b.1.rating.total_cmp(&a.1.rating));
+
+        writeln!(output, "  #  {:<24} {:>6} {:>6}", "Team", "Rating", "Last").unwrap();
+        for (position, (team, rating)) in teams.iter().enumerate() {
+            writeln!(
+                output,
+                "{:>3}  {:<24} {:>6.0} {:>+6.1}",
+                position + 1,
+                data.get_team(team).unwrap().get_name(),
+                rating.rating,
+                rating.get_change(),
+            )
+            .unwrap();
+        }
+
+        let mut players: Vec<_> = self.players.iter().collect();
+        players.sort_by(|a, b| b.1.rating.rating.total_cmp(&a.1.rating.rating));
+
+        writeln!(output).unwrap();
+        writeln!(
+            output,
+            "  #  {:<24} {:>6} {:>6}  Points won-lost",
+            "Player", "Rating", "Last"
+        )
+        .unwrap();
+        for (position, (player, rating)) in players.iter().enumerate() {
+            let points = rating
+                .points
+                .iter()
+                .map(|(team, (won, lost))| {
+                    format!(
+                        "{won}-{lost} for {}",
+                        data.get_team(team).unwrap().get_name()
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            writeln!(
+                output,
+                "{:>3}  {:<24} {:>6.0} {:>+6.1}  {points}",
+                position + 1,
+                data.get_player(player).unwrap().get_name(),
+                rating.rating.rating,
+                rating.rating.get_change(),
+            )
+            .unwrap();
+        }
+
+        output
+    }
+}
+
+pub fn run(options: &Options) {
+    let data = crate::load_league(options);
+    let ratings = data.get_ratings();
+
+    if ratings.games == 0 {
+        println!("Nobody has played a rated match yet.");
+    } else {
+        println!("Ratings after {} matches", ratings.games);
+        println!();
+        print!("{}", ratings.get_text(&data));
+    }
+}
@@ -0,0 +1,193 @@
+use std::{fmt::Display, fs, path::Path};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use rand_chacha::ChaCha20Rng;
+
+use crate::Data;
+
+/// Bumped whenever the layout of saved data changes. Leagues saved as JSON by any older version
+/// still load, so every field added to the league needs a `#[serde(default)]`. Binary saves and
+/// checkpoints don't say which fields they have, so only the current version can read them
+pub const FORMAT_VERSION: u32 = 17;
+
+/// The first version there was, which JSON leagues can still be loaded from
+const OLDEST_JSON_LEAGUE: u32 = 1;
+
+/// Put at the start of binary saves so they can't be confused with something else
+const MAGIC: &[u8; 4] = b"TLNS";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Format {
+    Json,
+    Binary,
+}
+
+impl Format {
+    /// `.json` files are saved as JSON, anything else is saved as binary
+    pub fn from_path(path: &Path) -> Self {
+        if path.extension().is_some_and(|x| x == "json") {
+            Self::Json
+        } else {
+            Self::Binary
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Encode(bincode::error::EncodeError),
+    Decode(bincode::error::DecodeError),
+    NotASave,
+    UnsupportedVersion(u32),
+}
+
+impl Display for SaveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "couldn't access the save: {err}"),
+            Self::Json(err) => write!(f, "invalid JSON save: {err}"),
+            Self::Encode(err) => write!(f, "couldn't encode the save: {err}"),
+            Self::Decode(err) => write!(f, "invalid binary save: {err}"),
+            Self::NotASave => write!(f, "not a save file"),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "save format version {version} is not supported (expected {FORMAT_VERSION})"
+            ),
+        }
+    }
+}
+
+impl From<std::io::Error> for SaveError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for SaveError {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Json(value)
+    }
+}
+
+impl From<bincode::error::EncodeError> for SaveError {
+    fn from(value: bincode::error::EncodeError) -> Self {
+        Self::Encode(value)
+    }
+}
+
+impl From<bincode::error::DecodeError> for SaveError {
+    fn from(value: bincode::error::DecodeError) -> Self {
+        Self::Decode(value)
+    }
+}
+
+#[derive(Serialize)]
+struct SaveFile<'a, T> {
+    version: u32,
+    data: &'a T,
+}
+
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+}
+
+#[derive(Deserialize)]
+struct LoadFile<T> {
+    data: T,
+}
+
+/// Saves from `oldest` up to the current version can be read
+const fn check_version(version: u32, oldest: u32) -> Result<(), SaveError> {
+    if version >= oldest && version <= FORMAT_VERSION {
+        Ok(())
+    } else {
+        Err(SaveError::UnsupportedVersion(version))
+    }
+}
+
+pub fn to_bytes<T: Serialize>(value: &T, format: Format) -> Result<Vec<u8>, SaveError> {
+    match format {
+        Format::Json => Ok(serde_json::to_vec_pretty(&SaveFile {
+            version: FORMAT_VERSION,
+            data: value,
+        })?),
+        Format::Binary => {
+            let config = bincode::config::standard();
+            let mut bytes = MAGIC.to_vec();
+            bytes.extend(bincode::serde::encode_to_vec(FORMAT_VERSION, config)?);
+            bytes.extend(bincode::serde::encode_to_vec(value, config)?);
+            Ok(bytes)
+        }
+    }
+}
+
+/// JSON saves from `oldest` on are read, binary saves have to be from the current version
+pub fn from_bytes<T: DeserializeOwned>(
+    bytes: &[u8],
+    format: Format,
+    oldest: u32,
+) -> Result<T, SaveError> {
+    match format {
+        Format::Json => {
+            let header: Header = serde_json::from_slice(bytes)?;
+            check_version(header.version, oldest)?;
+            let file: LoadFile<T> = serde_json::from_slice(bytes)?;
+            Ok(file.data)
+        }
+        Format::Binary => {
+            let config = bincode::config::standard();
+            let bytes = bytes.strip_prefix(MAGIC).ok_or(SaveError::NotASave)?;
+            let (version, read) = bincode::serde::decode_from_slice(bytes, config)?;
+            check_version(version, FORMAT_VERSION)?;
+            let (value, _) = bincode::serde::decode_from_slice(&bytes[read..], config)?;
+            Ok(value)
+        }
+    }
+}
+
+pub fn save(path: &Path, data: &Data) -> Result<(), SaveError> {
+    fs::write(path, to_bytes(data, Format::from_path(path))?)?;
+    Ok(())
+}
+
+pub fn load(path: &Path) -> Result<Data, SaveError> {
+    from_bytes(
+        &fs::read(path)?,
+        Format::from_path(path),
+        OLDEST_JSON_LEAGUE,
+    )
+}
+
+/// A match in progress, with everything needed to carry on exactly where it stopped
+#[derive(Deserialize)]
+pub struct Checkpoint<G> {
+    pub data: Data,
+    pub game: G,
+    pub rng: ChaCha20Rng,
+}
+
+#[derive(Serialize)]
+struct CheckpointRef<'a, G> {
+    data: &'a Data,
+    game: &'a G,
+    rng: &'a ChaCha20Rng,
+}
+
+pub fn save_checkpoint<G: Serialize>(
+    path: &Path,
+    data: &Data,
+    game: &G,
+    rng: &ChaCha20Rng,
+) -> Result<(), SaveError> {
+    let checkpoint = CheckpointRef { data, game, rng };
+    fs::write(path, to_bytes(&checkpoint, Format::from_path(path))?)?;
+    Ok(())
+}
+
+pub fn load_checkpoint<G: DeserializeOwned>(path: &Path) -> Result<Checkpoint<G>, SaveError> {
+    from_bytes(&fs::read(path)?, Format::from_path(path), FORMAT_VERSION)
+}
@@ -0,0 +1,414 @@
+use std::{cmp::Ordering, collections::BTreeMap, fmt::Write, sync::Arc};
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cli::Options,
+    game::{Court, Game, Rotation, Scoring, Side},
+    team::TeamId,
+    Data,
+};
+
+/// How a finished match ended
+#[derive(Clone, Copy, Debug)]
+pub struct MatchResult {
+    pub home: TeamId,
+    pub away: TeamId,
+    pub home_points: usize,
+    pub away_points: usize,
+    pub winner: Side,
+}
+
+impl MatchResult {
+    pub const fn from_game(game: &Game) -> Self {
+        let scoreboard = game.get_scoreboard();
+        Self {
+            home: game.get_team_id(Side::Home),
+            away: game.get_team_id(Side::Away),
+            home_points: scoreboard.get(Side::Home).total,
+            away_points: scoreboard.get(Side::Away).total,
+            winner: game.get_winner(),
+        }
+    }
+
+    pub const fn get_winner(&self) -> TeamId {
+        match self.winner {
+            Side::Home => self.home,
+            Side::Away => self.away,
+        }
+    }
+
+    pub fn get_text(&self, data: &Data) -> String {
+        let name = |id: &TeamId| data.get_team(id).unwrap().get_name();
+        format!(
+            "{} {} - {} {}",
+            name(&self.home),
+            self.home_points,
+            self.away_points,
+            name(&self.away)
+        )
+    }
+}
+
+/// Plays a whole match between two teams
+pub fn play_match<R: Rng>(
+    home: TeamId,
+    away: TeamId,
+    scoring: Scoring,
+    rotation: Rotation,
+    court: Court,
+    data: &mut Data,
+    rng: &mut R,
+) -> MatchResult {
+    let mut game = Game::new(home, away, data.get_climate().pick(rng))
+        .with_rotation(rotation)
+        .with_scoring(scoring)
+        .with_court(court);
+    game.play_out(data, rng);
+
+    MatchResult::from_game(&game)
+}
+
+/// An independent stream of randomness for each of the games played from the same seed
+pub fn stream(seed: u64, index: u64) -> ChaCha20Rng {
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
+    rng.set_stream(index + 1);
+    rng
+}
+
+/// A team's results over a season
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct Record {
+    pub wins: usize,
+    pub losses: usize,
+    pub points_for: usize,
+    pub points_against: usize,
+    /// Positive while on a winning streak, negative while on a losing streak
+    pub streak: isize,
+    /// How many times this team has beaten each other team
+    #[serde(default)]
+    pub beaten: BTreeMap<TeamId, usize>,
+}
+
+impl Record {
+    #[allow(clippy::cast_possible_wrap)]
+    pub const fn point_differential(&self) -> isize {
+        self.points_for as isize - self.points_against as isize
+    }
+
+    fn record(&mut self, opponent: TeamId, won: bool, points_for: usize, points_against: usize) {
+        self.points_for += points_for;
+        self.points_against += points_against;
+
+        if won {
+            self.wins += 1;
+            self.streak = self.streak.max(0) + 1;
+            *self.beaten.entry(opponent).or_default() += 1;
+        } else {
+            self.losses += 1;
+            self.streak = self.streak.min(0) - 1;
+        }
+    }
+
+    fn get_streak(&self) -> String {
+        match self.streak.cmp(&0) {
+            Ordering::Greater => format!("W{}", self.streak),
+            Ordering::Less => format!("L{}", -self.streak),
+            Ordering::Equal => "-".to_owned(),
+        }
+    }
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct Standings {
+    records: BTreeMap<TeamId, Record>,
+}
+
+impl Standings {
+    pub fn new(teams: &[TeamId]) -> Self {
+        Self {
+            records: teams.iter().map(|&x| (x, Record::default())).collect(),
+        }
+    }
+
+    pub fn record(&mut self, result: &MatchResult) {
+        self.records.entry(result.home).or_default().record(
+            result.away,
+            result.winner == Side::Home,
+            result.home_points,
+            result.away_points,
+        );
+        self.records.entry(result.away).or_default().record(
+            result.home,
+            result.winner == Side::Away,
+            result.away_points,
+            result.home_points,
+        );
+    }
+
+    /// A round without a game, which counts as a win
+    pub fn record_bye(&mut self, team: TeamId) {
+        let record = self.records.entry(team).or_default();
+        record.wins += 1;
+        record.streak = record.streak.max(0) + 1;
+    }
+
+    /// Teams from best to worst: most wins first. Teams with as many wins are ordered by the
+    /// games they won against each other, then by point differential, then by points scored
+    pub fn ranking(&self) -> Vec<TeamId> {
+        let mut teams: Vec<_> = self.records.keys().copied().collect();
+        teams.sort_by(|a, b| self.records[b].wins.cmp(&self.records[a].wins));
+
+        for tied in teams.chunk_by_mut(|a, b| self.records[a].wins == self.records[b].wins) {
+            let group = tied.to_vec();
+            let head_to_head = |team: &TeamId| -> usize {
+                group
+                    .iter()
+                    .filter_map(|other| self.records[team].beaten.get(other))
+                    .sum()
+            };
+
+            tied.sort_by(|a, b| {
+                let (a_record, b_record) = (&self.records[a], &self.records[b]);
+                head_to_head(b)
+                    .cmp(&head_to_head(a))
+                    .then(
+                        b_record
+                            .point_differential()
+                            .cmp(&a_record.point_differential()),
+                    )
+                    .then(b_record.points_for.cmp(&a_record.points_for))
+            });
+        }
+
+        teams
+    }
+
+    pub fn get_text(&self, data: &Data) -> String {
+        let mut output = String::new();
+        writeln!(
+            output,
+            "  #  {:<24} {:>3} {:>3} {:>5} {:>5} {:>5}",
+            "Team", "W", "L", "PF", "PA", "Strk"
+        )
+        .unwrap();
+
+        for (position, team) in self.ranking().iter().enumerate() {
+            let record = &self.records[team];
+            writeln!(
+                output,
+                "{:>3}  {:<24} {:>3} {:>3} {:>5} {:>5} {:>5}",
+                position + 1,
+                data.get_team(team).unwrap().get_name(),
+                record.wins,
+                record.losses,
+                record.points_for,
+                record.points_against,
+                record.get_streak(),
+            )
+            .unwrap();
+        }
+
+        output
+    }
+}
+
+/// Pairs every team with every other team once, one round per day.
+/// With an odd number of teams, one team rests each day
+pub fn round_robin(teams: &[TeamId]) -> Vec<Vec<(TeamId, TeamId)>> {
+    let mut slots: Vec<Option<TeamId>> = teams.iter().copied().map(Some).collect();
+    if slots.len() % 2 == 1 {
+        slots.push(None);
+    }
+    if slots.len() < 2 {
+        return vec![];
+    }
+
+    let half = slots.len() / 2;
+    let mut days = vec![];
+
+    for day in 0..slots.len() - 1 {
+        let mut games = vec![];
+        for index in 0..half {
+            if let (Some(a), Some(b)) = (slots[index], slots[slots.len() - 1 - index]) {
+                // Swap sides every other day so nobody is always at home
+                games.push(if day % 2 == 0 { (a, b) } else { (b, a) });
+            }
+        }
+        days.push(games);
+
+        // Keep the first team in place and rotate everybody else
+        slots[1..].rotate_right(1);
+    }
+
+    days
+}
+
+const fn classic_court() -> Court {
+    Court::CLASSIC
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Season {
+    seed: u64,
+    scoring: Scoring,
+    rotation: Rotation,
+    /// Seasons saved before courts could be changed are played on the classic court
+    #[serde(default = "classic_court")]
+    court: Court,
+    teams: Vec<TeamId>,
+    schedule: Vec<Vec<(TeamId, TeamId)>>,
+    day: usize,
+    games_played: u64,
+    standings: Standings,
+}
+
+impl Season {
+    pub fn new(
+        teams: Vec<TeamId>,
+        seed: u64,
+        scoring: Scoring,
+        rotation: Rotation,
+        court: Court,
+    ) -> Self {
+        Self {
+            seed,
+            scoring,
+            rotation,
+            court,
+            schedule: round_robin(&teams),
+            standings: Standings::new(&teams),
+            teams,
+            day: 0,
+            games_played: 0,
+        }
+    }
+
+    pub const fn is_finished(&self) -> bool {
+        self.day >= self.schedule.len()
+    }
+
+    pub const fn get_days(&self) -> usize {
+        self.schedule.len()
+    }
+
+    pub const fn get_standings(&self) -> &Standings {
+        &self.standings
+    }
+
+    fn next_rng(&mut self) -> ChaCha20Rng {
+        self.games_played += 1;
+        stream(self.seed, self.games_played - 1)
+    }
+
+    /// Plays all of the current day's games and moves on to the next day
+    pub fn play_day(&mut self, data: &mut Data) -> Vec<MatchResult> {
+        let Some(games) = self.schedule.get(self.day).cloned() else {
+            return vec![];
+        };
+
+        let mut results = vec![];
+        for (home, away) in games {
+            let mut rng = self.next_rng();
+            let result = play_match(
+                home,
+                away,
+                self.scoring,
+                self.rotation,
+                self.court,
+                data,
+                &mut rng,
+            );
+            self.standings.record(&result);
+            results.push(result);
+        }
+
+        self.day += 1;
+        data.pass_day();
+        results
+    }
+}
+
+/// Every injured player in the given teams, with how long they'll be out for
+fn get_injured_text(teams: &[TeamId], data: &Data) -> String {
+    teams
+        .iter()
+        .flat_map(|team| data.get_team(team).unwrap().get_players())
+        .filter_map(|id| {
+            let player = data.get_player(id).unwrap();
+            let days = player.get_condition().injured_days;
+            (days > 0).then(|| {
+                format!(
+                    "{} ({days} day{})",
+                    player.get_name(),
+                    if days == 1 { "" } else { "s" }
+                )
+            })
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub fn run(options: &Options, team_count: usize, days: Option<usize>) {
+    let mut data = crate::load_league(options);
+
+    let mut season = match data.season.take() {
+        Some(season) if !season.is_finished() => Arc::unwrap_or_clone(season),
+        _ => {
+            let seed = options.seed.unwrap_or_else(rand::random);
+            let mut rng = ChaCha20Rng::seed_from_u64(seed);
+
+            let teams = data.fill_teams(team_count, &mut rng);
+
+            let season = Season::new(
+                teams,
+                seed,
+                options.scoring,
+                options.rotation,
+                options.court,
+            );
+            println!("Seed: {seed}");
+            println!(
+                "A new season begins with {} teams over {} days!",
+                season.teams.len(),
+                season.get_days()
+            );
+            season
+        }
+    };
+
+    let days = days.unwrap_or(usize::MAX);
+    for _ in 0..days {
+        if season.is_finished() {
+            break;
+        }
+
+        println!();
+        println!("Day {} of {}", season.day + 1, season.get_days());
+        for result in season.play_day(&mut data) {
+            println!("  {}", result.get_text(&data));
+        }
+        println!();
+        print!("{}", season.standings.get_text(&data));
+
+        let injured = get_injured_text(&season.teams, &data);
+        if !injured.is_empty() {
+            println!("Injured: {injured}");
+        }
+    }
+
+    if season.is_finished() {
+        if let Some(champion) = season.standings.ranking().first() {
+            println!();
+            println!(
+                "The season is over! {} finish on top.",
+                data.get_team(champion).unwrap().get_name()
+            );
+        }
+    }
+
+    data.season = Some(Arc::new(season));
+    crate::save_league(options, &data);
+}
@@ -0,0 +1,200 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use rayon::prelude::*;
+
+use crate::{
+    cli::Options,
+    game::{Game, GameEvent},
+    season::stream,
+    team::TeamId,
+    Data,
+};
+
+/// What happened over many matches between the same two teams
+pub struct Stats {
+    home: TeamId,
+    away: TeamId,
+    matches: usize,
+    home_wins: usize,
+    points: usize,
+    hits: usize,
+    longest_rally: usize,
+    aces: usize,
+    double_faults: usize,
+    /// How many matches ended with each (home, away) result
+    final_scores: BTreeMap<(usize, usize), usize>,
+    weather_effects: BTreeMap<&'static str, usize>,
+}
+
+impl Stats {
+    pub const fn new(home: TeamId, away: TeamId) -> Self {
+        Self {
+            home,
+            away,
+            matches: 0,
+            home_wins: 0,
+            points: 0,
+            hits: 0,
+            longest_rally: 0,
+            aces: 0,
+            double_faults: 0,
+            final_scores: BTreeMap::new(),
+            weather_effects: BTreeMap::new(),
+        }
+    }
+
+    /// Adds up the stats of two sets of matches between the same teams
+    pub fn merge(mut self, other: Self) -> Self {
+        self.matches += other.matches;
+        self.home_wins += other.home_wins;
+        self.points += other.points;
+        self.hits += other.hits;
+        self.longest_rally = self.longest_rally.max(other.longest_rally);
+        self.aces += other.aces;
+        self.double_faults += other.double_faults;
+        for (score, count) in other.final_scores {
+            *self.final_scores.entry(score).or_default() += count;
+        }
+        for (effect, count) in other.weather_effects {
+            *self.weather_effects.entry(effect).or_default() += count;
+        }
+        self
+    }
+
+    fn record_event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::Hit { .. }
+            | GameEvent::Lob { .. }
+            | GameEvent::DropShot { .. }
+            | GameEvent::PowerShot { .. } => self.hits += 1,
+            GameEvent::Score { .. } => self.points += 1,
+            GameEvent::Ace { .. } => self.aces += 1,
+            GameEvent::DoubleFault { .. } => self.double_faults += 1,
+            _ => (),
+        }
+
+        if let Some(effect) = event.weather_effect() {
+            *self.weather_effects.entry(effect).or_default() += 1;
+        }
+    }
+
+    /// Plays `game` to the end and records everything that happens in it
+    pub fn record_match<R: Rng>(&mut self, mut game: Game, data: &mut Data, rng: &mut R) {
+        for report in game.play_out(data, rng) {
+            self.record_event(&report.moment.event);
+        }
+
+        self.longest_rally = self.longest_rally.max(game.get_longest_rally());
+
+        let (home, away) = game.get_scoreboard().result();
+        if home > away {
+            self.home_wins += 1;
+        }
+        *self.final_scores.entry((home, away)).or_default() += 1;
+        self.matches += 1;
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    pub fn get_text(&self, data: &Data) -> String {
+        let home_name = data.get_team(&self.home).unwrap().get_name();
+        let away_name = data.get_team(&self.away).unwrap().get_name();
+        let matches = self.matches.max(1) as f64;
+        let percentage = |x: usize| 100.0 * x as f64 / matches;
+
+        let mut output = String::new();
+        writeln!(
+            output,
+            "{home_name} vs {away_name}, {} matches",
+            self.matches
+        )
+        .unwrap();
+        writeln!(output, "{home_name} win {:.1}%", percentage(self.home_wins)).unwrap();
+        writeln!(
+            output,
+            "{away_name} win {:.1}%",
+            percentage(self.matches - self.home_wins)
+        )
+        .unwrap();
+        writeln!(
+            output,
+            "Points per match: {:.2}",
+            self.points as f64 / matches
+        )
+        .unwrap();
+        writeln!(
+            output,
+            "Rally length: {:.2} hits",
+            self.hits as f64 / self.points.max(1) as f64
+        )
+        .unwrap();
+
+        writeln!(output, "Longest rally: {} hits", self.longest_rally).unwrap();
+        writeln!(output, "Aces per match: {:.2}", self.aces as f64 / matches).unwrap();
+        writeln!(
+            output,
+            "Double faults per match: {:.2}",
+            self.double_faults as f64 / matches
+        )
+        .unwrap();
+
+        writeln!(output, "Final scores:").unwrap();
+        for (&(home, away), &count) in &self.final_scores {
+            writeln!(
+                output,
+                "  {home}-{away}: {count} ({:.1}%)",
+                percentage(count)
+            )
+            .unwrap();
+        }
+
+        writeln!(output, "Weather effects:").unwrap();
+        for (effect, &count) in &self.weather_effects {
+            writeln!(
+                output,
+                "  {effect}: {count} ({:.2} per match)",
+                count as f64 / matches
+            )
+            .unwrap();
+        }
+
+        output
+    }
+}
+
+pub fn run(options: &Options, matches: usize, threads: Option<usize>) {
+    let seed = options.seed.unwrap_or_else(rand::random);
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
+
+    let (data, home, away) = crate::setup(options, &mut rng);
+    println!("Seed: {seed}");
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads.unwrap_or(0))
+        .build()
+        .unwrap();
+
+    // Every match gets its own copy of the rosters and its own stream of randomness, and the
+    // stats only ever get added up, so the result doesn't depend on how the matches are split
+    let stats = pool.install(|| {
+        (0..matches)
+            .into_par_iter()
+            .map(|index| {
+                let mut data = data.clone();
+                let mut rng = stream(seed, index as u64);
+
+                let game = Game::new(home, away, data.get_climate().pick(&mut rng))
+                    .with_rotation(options.rotation)
+                    .with_scoring(options.scoring)
+                    .with_court(options.court);
+
+                let mut stats = Stats::new(home, away);
+                stats.record_match(game, &mut data, &mut rng);
+                stats
+            })
+            .reduce(|| Stats::new(home, away), Stats::merge)
+    });
+
+    print!("{}", stats.get_text(&data));
+}
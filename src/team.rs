@@ -1,14 +1,20 @@
 use rand::prelude::SliceRandom;
 use rand::Rng;
-use uuid::Uuid;
+use serde::{Deserialize, Serialize};
+use uuid::{Builder, Uuid};
 
-use crate::{player::PlayerId, Data};
+use crate::{
+    player::{Player, PlayerId},
+    Data,
+};
 
-#[derive(PartialEq, Eq, PartialOrd, Hash, Clone, Copy, Debug)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct TeamId {
     uuid: Uuid,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Team {
     name: String,
     players: Vec<PlayerId>,
@@ -16,9 +22,9 @@ pub struct Team {
 }
 
 impl TeamId {
-    pub fn new() -> Self {
+    pub fn new<R: Rng>(rng: &mut R) -> Self {
         Self {
-            uuid: Uuid::new_v4(),
+            uuid: Builder::from_random_bytes(rng.gen()).into_uuid(),
         }
     }
     pub fn get_current_player(&self, data: &Data) -> Option<PlayerId> {
@@ -29,9 +35,52 @@ impl TeamId {
         data.get_team_mut(self)
             .and_then(|x| x.get_current_player_mut())
     }
+
+    /// Moves the team on to the next player in its lineup who isn't injured
+    pub fn rotate(&self, data: &mut Data) {
+        self.rotate_past(data, |_, player| player.is_injured());
+    }
+
+    /// Moves the team on to the next healthy player in its lineup who isn't in `on_court`.
+    /// Returns them, unless there's nobody like that, in which case the team stays as it was
+    pub fn next_healthy_player(&self, on_court: &[PlayerId], data: &mut Data) -> Option<PlayerId> {
+        self.rotate_past(data, |id, player| {
+            player.is_injured() || on_court.contains(id)
+        })
+    }
+
+    /// Moves the team on to the next player in its lineup who isn't in `on_court`, healthy or not
+    pub fn next_player(&self, on_court: &[PlayerId], data: &mut Data) -> Option<PlayerId> {
+        self.rotate_past(data, |id, _| on_court.contains(id))
+    }
+
+    fn rotate_past(
+        &self,
+        data: &mut Data,
+        skip: impl Fn(&PlayerId, &Player) -> bool,
+    ) -> Option<PlayerId> {
+        let team = data.get_team(self)?;
+        let skipped: Vec<_> = team
+            .players
+            .iter()
+            .map(|x| skip(x, data.get_player(x).unwrap()))
+            .collect();
+
+        let team = data.get_team_mut(self).unwrap();
+        team.rotate(&skipped)
+            .then(|| team.get_current_player().unwrap())
+    }
 }
 
 impl Team {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_players(&self) -> &[PlayerId] {
+        &self.players
+    }
+
     pub fn get_current_player(&self) -> Option<PlayerId> {
         self.players.get(self.current_player).copied()
     }
@@ -52,6 +101,32 @@ impl Team {
             current_player: 0,
         }
     }
+    /// Moves on to the next player in the lineup that isn't `skipped`, coming back around to the
+    /// current player last. Returns whether there was anybody
+    fn rotate(&mut self, skipped: &[bool]) -> bool {
+        let start = self.current_player;
+        for offset in 1..=self.players.len() {
+            let index = (start + offset) % self.players.len();
+            if !skipped[index] {
+                self.current_player = index;
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Puts `new` in the lineup wherever `old` was
+    pub fn replace_player(&mut self, old: PlayerId, new: PlayerId) {
+        for player in self.players.iter_mut().filter(|x| **x == old) {
+            *player = new;
+        }
+    }
+
+    /// Puts a new player at the end of the lineup
+    pub fn add_player(&mut self, player: PlayerId) {
+        self.players.push(player);
+    }
+
     pub fn shuffle_players<R: Rng>(&mut self, rng: &mut R) {
         self.players.shuffle(rng);
     }
@@ -0,0 +1,280 @@
+use std::{collections::BTreeSet, str::FromStr};
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+use crate::{
+    cli::Options,
+    game::{Court, Rotation, Scoring},
+    season::{play_match, round_robin, stream, MatchResult, Standings},
+    team::TeamId,
+    Data,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum System {
+    /// Every round, teams play others with a similar record that they haven't played yet
+    Swiss,
+    /// Every team plays every other team once
+    RoundRobin,
+}
+
+impl FromStr for System {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "swiss" => Ok(Self::Swiss),
+            "round-robin" => Ok(Self::RoundRobin),
+            _ => Err(format!("unknown tournament system: {s}")),
+        }
+    }
+}
+
+/// The same key for a pair of teams no matter who was at home
+fn matchup(a: TeamId, b: TeamId) -> (TeamId, TeamId) {
+    (a.min(b), a.max(b))
+}
+
+/// Pairs up `teams` in order, each with the best team left it hasn't played yet.
+/// Backtracks when that leaves someone without an opponent
+fn pair_up(teams: &[TeamId], played: &BTreeSet<(TeamId, TeamId)>) -> Option<Vec<(TeamId, TeamId)>> {
+    let Some((&first, rest)) = teams.split_first() else {
+        return Some(vec![]);
+    };
+
+    for (index, &other) in rest.iter().enumerate() {
+        if played.contains(&matchup(first, other)) {
+            continue;
+        }
+
+        let mut remaining = rest.to_vec();
+        remaining.remove(index);
+        if let Some(mut pairs) = pair_up(&remaining, played) {
+            pairs.insert(0, (first, other));
+            return Some(pairs);
+        }
+    }
+
+    None
+}
+
+/// The games of a round, and the team that didn't play in it
+pub struct Round {
+    pub results: Vec<MatchResult>,
+    pub bye: Option<TeamId>,
+}
+
+pub struct Tournament {
+    system: System,
+    scoring: Scoring,
+    rotation: Rotation,
+    court: Court,
+    seed: u64,
+    games_played: u64,
+    teams: Vec<TeamId>,
+    rounds: usize,
+    round: usize,
+    /// Only used by round robins, Swiss rounds are paired as they come
+    schedule: Vec<Vec<(TeamId, TeamId)>>,
+    played: BTreeSet<(TeamId, TeamId)>,
+    /// Teams that have already had a bye, which nobody gets twice if it can be helped
+    byes: BTreeSet<TeamId>,
+    standings: Standings,
+}
+
+impl Tournament {
+    /// Swiss tournaments last `rounds` rounds, or as many as a knockout between the same teams
+    /// would when not given
+    pub fn new(
+        teams: Vec<TeamId>,
+        system: System,
+        rounds: Option<usize>,
+        scoring: Scoring,
+        rotation: Rotation,
+        court: Court,
+        seed: u64,
+    ) -> Self {
+        let schedule = match system {
+            System::Swiss => vec![],
+            System::RoundRobin => round_robin(&teams),
+        };
+        let rounds = match system {
+            System::Swiss => {
+                rounds.unwrap_or_else(|| teams.len().next_power_of_two().trailing_zeros() as usize)
+            }
+            System::RoundRobin => schedule.len(),
+        };
+
+        Self {
+            system,
+            scoring,
+            rotation,
+            court,
+            seed,
+            games_played: 0,
+            standings: Standings::new(&teams),
+            teams,
+            rounds,
+            round: 0,
+            schedule,
+            played: BTreeSet::new(),
+            byes: BTreeSet::new(),
+        }
+    }
+
+    pub const fn is_finished(&self) -> bool {
+        self.round >= self.rounds
+    }
+
+    pub const fn get_round(&self) -> usize {
+        self.round
+    }
+
+    pub const fn get_rounds(&self) -> usize {
+        self.rounds
+    }
+
+    pub const fn get_standings(&self) -> &Standings {
+        &self.standings
+    }
+
+    /// Pairs teams by their record so far. With an odd number of teams, the worst team that
+    /// hasn't had a bye yet gets one
+    fn swiss_pairings(&self) -> (Vec<(TeamId, TeamId)>, Option<TeamId>) {
+        let mut ranking = self.standings.ranking();
+
+        let bye = (ranking.len() % 2 == 1).then(|| {
+            let index = ranking
+                .iter()
+                .rposition(|x| !self.byes.contains(x))
+                .unwrap_or(ranking.len() - 1);
+            ranking.remove(index)
+        });
+
+        // Rematches only happen once every possible pairing has been used
+        let pairs = pair_up(&ranking, &self.played)
+            .unwrap_or_else(|| ranking.chunks(2).map(|x| (x[0], x[1])).collect());
+
+        (pairs, bye)
+    }
+
+    /// Plays every game of the next round
+    pub fn play_round(&mut self, data: &mut Data) -> Round {
+        if self.is_finished() {
+            return Round {
+                results: vec![],
+                bye: None,
+            };
+        }
+
+        let (pairs, bye) = match self.system {
+            System::Swiss => self.swiss_pairings(),
+            System::RoundRobin => {
+                let pairs = self.schedule[self.round].clone();
+                let bye = self
+                    .teams
+                    .iter()
+                    .find(|x| !pairs.iter().any(|(home, away)| home == *x || away == *x))
+                    .copied();
+                (pairs, bye)
+            }
+        };
+        self.round += 1;
+
+        if let Some(team) = bye {
+            self.byes.insert(team);
+            // Everybody sits out once in a round robin, so it's only worth a win in Swiss
+            if self.system == System::Swiss {
+                self.standings.record_bye(team);
+            }
+        }
+
+        let mut results = vec![];
+        for (home, away) in pairs {
+            let mut rng = stream(self.seed, self.games_played);
+            self.games_played += 1;
+
+            let result = play_match(
+                home,
+                away,
+                self.scoring,
+                self.rotation,
+                self.court,
+                data,
+                &mut rng,
+            );
+            self.played.insert(matchup(home, away));
+            self.standings.record(&result);
+            results.push(result);
+        }
+
+        data.pass_day();
+        Round { results, bye }
+    }
+
+    pub fn get_bye_text(&self, team: &TeamId, data: &Data) -> String {
+        let name = data.get_team(team).unwrap().get_name();
+        match self.system {
+            System::Swiss => format!("{name} get a bye."),
+            System::RoundRobin => format!("{name} sit this round out."),
+        }
+    }
+}
+
+pub fn run(options: &Options, system: System, rounds: Option<usize>, team_count: usize) {
+    let mut data = crate::load_league(options);
+
+    let seed = options.seed.unwrap_or_else(rand::random);
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
+    println!("Seed: {seed}");
+
+    let mut teams = data.fill_teams(team_count, &mut rng);
+    teams.truncate(team_count);
+
+    let mut tournament = Tournament::new(
+        teams,
+        system,
+        rounds,
+        options.scoring,
+        options.rotation,
+        options.court,
+        seed,
+    );
+    println!(
+        "A tournament begins with {} teams over {} rounds!",
+        tournament.teams.len(),
+        tournament.get_rounds()
+    );
+
+    while !tournament.is_finished() {
+        let round = tournament.play_round(&mut data);
+
+        println!();
+        println!(
+            "Round {} of {}",
+            tournament.get_round(),
+            tournament.get_rounds()
+        );
+        for result in &round.results {
+            println!("  {}", result.get_text(&data));
+        }
+        if let Some(team) = &round.bye {
+            println!("  {}", tournament.get_bye_text(team, &data));
+        }
+        println!();
+        print!("{}", tournament.get_standings().get_text(&data));
+    }
+
+    println!();
+    println!("Final rankings");
+    for (position, team) in tournament.get_standings().ranking().iter().enumerate() {
+        println!(
+            "{:>3}  {}",
+            position + 1,
+            data.get_team(team).unwrap().get_name()
+        );
+    }
+
+    crate::save_league(options, &data);
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use common::run;
+
+// Columns of the stats table, from the right
+const PL: usize = 2;
+const PW: usize = 3;
+const DF: usize = 7;
+const FLT: usize = 8;
+const ACE: usize = 9;
+
+/// The total of one of the columns of the stats table
+fn column(output: &str, index: usize) -> usize {
+    output
+        .lines()
+        .skip(1)
+        .map(|line| {
+            let line = line.trim_end_matches("  Replaced");
+            line.split_whitespace()
+                .rev()
+                .nth(index)
+                .unwrap()
+                .parse::<usize>()
+                .unwrap()
+        })
+        .sum()
+}
+
+#[test]
+fn career_stats_add_up_over_a_season() {
+    let league = std::env::temp_dir().join("blaseball-test-career.json");
+    let _ = std::fs::remove_file(&league);
+
+    assert_eq!(run(&league, &["stats"]), "Nobody has played a match yet.\n");
+
+    run(&league, &["season", "--seed", "8", "--teams", "4"]);
+    let output = run(&league, &["stats"]);
+    std::fs::remove_file(&league).unwrap();
+
+    // Every point somebody wins, somebody else loses
+    assert!(column(&output, PW) > 0);
+    assert_eq!(column(&output, PW), column(&output, PL));
+}
+
+#[test]
+fn career_serves_match_the_feed() {
+    let league = std::env::temp_dir().join("blaseball-test-career-serves.json");
+    let _ = std::fs::remove_file(&league);
+
+    let feed = run(&league, &["--seed", "1", "--no-delay"]);
+    let output = run(&league, &["stats"]);
+    std::fs::remove_file(&league).unwrap();
+
+    // A double fault is the second fault of the point, so it only counts once
+    let double_faults = feed.matches(" double faults!").count();
+    assert!(double_faults > 0);
+    assert_eq!(column(&output, DF), double_faults);
+    assert_eq!(column(&output, FLT), feed.matches(" faults!").count());
+    assert_eq!(
+        column(&output, ACE),
+        feed.matches(" serves an ace ").count()
+    );
+}
@@ -0,0 +1,14 @@
+use std::{path::Path, process::Command};
+
+/// Runs the game on the league, returning everything it printed
+pub fn run(league: &Path, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_blaseball"))
+        .args(args)
+        .arg("--league")
+        .arg(league)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    String::from_utf8(output.stdout).unwrap()
+}
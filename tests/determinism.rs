@@ -0,0 +1,46 @@
+use std::process::Command;
+
+fn run(seed: &str) -> Vec<u8> {
+    let output = Command::new(env!("CARGO_BIN_EXE_blaseball"))
+        .args(["--seed", seed, "--no-delay", "--scoring", "best-of-3"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    output.stdout
+}
+
+#[test]
+fn same_seed_same_feed() {
+    assert_eq!(run("1234"), run("1234"));
+}
+
+#[test]
+fn different_seed_different_feed() {
+    assert_ne!(run("1234"), run("4321"));
+}
+
+#[test]
+fn resumed_game_matches_uninterrupted_game() {
+    let checkpoint = std::env::temp_dir().join("blaseball-test-checkpoint.json");
+    let _ = std::fs::remove_file(&checkpoint);
+
+    let play = |extra: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_blaseball"))
+            .args(["--seed", "99", "--no-delay", "--scoring", "best-of-3"])
+            .arg("--checkpoint")
+            .arg(&checkpoint)
+            .args(extra)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        output.stdout
+    };
+
+    let mut resumed = play(&["--stop-after", "20"]);
+    assert!(checkpoint.exists());
+    resumed.extend(play(&[]));
+    assert!(!checkpoint.exists());
+
+    assert_eq!(resumed, run("99"));
+}
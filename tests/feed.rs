@@ -0,0 +1,372 @@
+use std::process::Command;
+
+#[test]
+fn jsonl_feed_is_one_report_per_line() {
+    let output = Command::new(env!("CARGO_BIN_EXE_blaseball"))
+        .args(["--seed", "5", "--no-delay", "--format", "jsonl"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let feed = String::from_utf8(output.stdout).unwrap();
+    let mut last_tick = 0;
+    for line in feed.lines() {
+        let report: serde_json::Value = serde_json::from_str(line).unwrap();
+
+        let tick = report["tick"].as_u64().unwrap();
+        assert!(tick >= last_tick);
+        last_tick = tick;
+
+        assert!(report["home"]["player_name"].is_string());
+        assert!(report["away"]["team_name"].is_string());
+        assert!(report["comment"].is_string());
+        assert!(report["rally"].is_u64());
+        assert!((1..=5).contains(&report["home"]["player_stars"].as_u64().unwrap()));
+    }
+    assert!(last_tick > 0);
+}
+
+#[test]
+fn headless_prints_only_the_result() {
+    let output = Command::new(env!("CARGO_BIN_EXE_blaseball"))
+        .args(["--seed", "5", "--headless"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let result = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(result.matches("+++++++++++++++++++++++++").count(), 1);
+    assert!(result.contains("wins!"));
+}
+
+#[test]
+fn quiet_prints_nothing() {
+    let output = Command::new(env!("CARGO_BIN_EXE_blaseball"))
+        .args(["--seed", "5", "--quiet"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn box_score_follows_the_feed() {
+    let output = Command::new(env!("CARGO_BIN_EXE_blaseball"))
+        .args(["--seed", "5", "--no-delay"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let feed = String::from_utf8(output.stdout).unwrap();
+    let (reports, box_score) = feed.split_once("=========================\n").unwrap();
+    assert!(reports.contains("wins!"));
+    assert!(box_score.starts_with("Final: The Speedles "));
+    assert!(box_score.contains("Longest rally: "));
+    assert!(box_score.contains("Weather: "));
+}
+
+#[test]
+fn doubles_puts_two_players_on_each_side() {
+    let output = Command::new(env!("CARGO_BIN_EXE_blaseball"))
+        .args([
+            "play",
+            "--seed",
+            "5",
+            "--no-delay",
+            "--format",
+            "jsonl",
+            "--kind",
+            "doubles",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let feed = String::from_utf8(output.stdout).unwrap();
+    for line in feed.lines() {
+        let report: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert_ne!(report["home"]["player"], report["home_partner"]["player"]);
+        assert_ne!(report["away"]["player"], report["away_partner"]["player"]);
+        assert_eq!(
+            report["home"]["team"], report["home_partner"]["team"],
+            "partners play for the same team"
+        );
+    }
+    assert!(feed.contains("wins!"));
+}
+
+#[test]
+fn bad_arguments_are_explained() {
+    let output = Command::new(env!("CARGO_BIN_EXE_blaseball"))
+        .args(["--rotation", "rallies:0"])
+        .env("RUST_BACKTRACE", "1")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+
+    let error = String::from_utf8(output.stderr).unwrap();
+    assert!(error.contains("unknown rotation policy: rallies:0"));
+    assert!(error.contains("usage: blaseball"));
+    assert!(!error.contains("panicked"));
+}
+
+#[test]
+fn unknown_game_kinds_are_rejected() {
+    let output = Command::new(env!("CARGO_BIN_EXE_blaseball"))
+        .args(["play", "--kind", "croquet"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+
+    let error = String::from_utf8(output.stderr).unwrap();
+    assert!(error.contains("unknown game kind: croquet"));
+    assert!(error.contains("singles"));
+    assert!(error.contains("doubles"));
+}
+
+#[test]
+fn only_single_matches_pick_a_kind() {
+    let output = Command::new(env!("CARGO_BIN_EXE_blaseball"))
+        .args(["season", "--kind", "doubles"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+
+    let error = String::from_utf8(output.stderr).unwrap();
+    assert!(error.contains("seasons, playoffs, tournaments and simulations always play singles"));
+}
+
+#[test]
+fn players_stay_on_the_grid() {
+    let output = Command::new(env!("CARGO_BIN_EXE_blaseball"))
+        .args([
+            "--seed",
+            "5",
+            "--no-delay",
+            "--format",
+            "jsonl",
+            "--court",
+            "4x3",
+            "--scoring",
+            "best-of-3",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let feed = String::from_utf8(output.stdout).unwrap();
+    let mut depths = vec![];
+    for line in feed.lines() {
+        let report: serde_json::Value = serde_json::from_str(line).unwrap();
+        for side in ["home", "away"] {
+            let space = &report[side]["space"];
+            assert!(space["lane"].as_u64().unwrap() < 4);
+            assert!(space["depth"].as_u64().unwrap() < 3);
+            depths.push(space["depth"].as_u64().unwrap());
+        }
+    }
+    assert!(
+        depths.contains(&0),
+        "somebody should have been drawn to the net"
+    );
+}
+
+#[test]
+fn courts_have_a_size_limit() {
+    for court in ["0x3", "3x0", "10x3", "3x10", "100000x100000"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_blaseball"))
+            .args(["--seed", "5", "--no-delay", "--court", court])
+            .output()
+            .unwrap();
+        assert!(!output.status.success(), "{court} should be rejected");
+
+        let error = String::from_utf8(output.stderr).unwrap();
+        assert!(error.contains("between 1 and 9"), "{error}");
+    }
+
+    // Nobody can ever miss on a court with one space, so the match would never end
+    let output = Command::new(env!("CARGO_BIN_EXE_blaseball"))
+        .args(["--seed", "5", "--no-delay", "--court", "1x1"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    let error = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        error.contains("court 1x1 needs at least two spaces"),
+        "{error}"
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_blaseball"))
+        .args(["--seed", "5", "--no-delay", "--court", "9x9"])
+        .args(["--scoring", "best-of-3"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+}
+
+#[test]
+fn the_classic_court_has_no_depth() {
+    let output = Command::new(env!("CARGO_BIN_EXE_blaseball"))
+        .args(["--seed", "5", "--no-delay", "--scoring", "best-of-3"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let feed = String::from_utf8(output.stdout).unwrap();
+    assert!(!feed.contains("lobs it over!"));
+    assert!(!feed.contains("drops it short!"));
+}
+
+#[test]
+fn players_run_down_long_balls() {
+    let output = Command::new(env!("CARGO_BIN_EXE_blaseball"))
+        .args([
+            "--seed",
+            "5",
+            "--no-delay",
+            "--format",
+            "jsonl",
+            "--court",
+            "grid",
+            "--scoring",
+            "best-of-3",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let feed = String::from_utf8(output.stdout).unwrap();
+    let chases: Vec<_> = feed
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .filter_map(|report| report["event"]["Chase"]["steps_left"].as_u64())
+        .collect();
+    assert!(
+        chases.contains(&0),
+        "somebody should have caught up to a ball"
+    );
+    assert!(
+        chases.iter().any(|&x| x > 0),
+        "somebody should still be running"
+    );
+}
+
+/// Plays a match with the rotation policy, returning how many points were scored between each
+/// time both teams sent their next player in and how many times the serve changed sides, and
+/// checking the reports show the new players
+fn points_between_rotations(rotation: &str, scoring: &str) -> (Vec<usize>, usize) {
+    let output = Command::new(env!("CARGO_BIN_EXE_blaseball"))
+        .args(["--seed", "5", "--no-delay", "--format", "jsonl"])
+        .args(["--rotation", rotation, "--scoring", scoring])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let feed: Vec<serde_json::Value> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|x| serde_json::from_str(x).unwrap())
+        .collect();
+
+    let mut rotations = vec![];
+    let mut points = 0;
+    let mut server = None;
+    let mut serve_changes = 0;
+    for (i, report) in feed.iter().enumerate() {
+        if report["event"]["Score"].is_object() {
+            points += 1;
+        }
+        // Faults and lets are serves too, even when there's no serve in the feed after them
+        let serve = ["Serve", "Fault", "DoubleFault", "Let"]
+            .into_iter()
+            .find(|&x| report["event"][x].is_object());
+        if let Some(serve) = serve {
+            let home = report["event"][serve]["player"] == report["home"]["player"];
+            if server.is_some_and(|x| x != home) {
+                serve_changes += 1;
+            }
+            server = Some(home);
+        }
+        let step_up = |report: &serde_json::Value, side: &str| {
+            let event = &report["event"]["StepUp"];
+            event.is_object() && event["team"] == report[side]["team"]
+        };
+        // Injuries send one player in at a time, rotations send in both
+        if step_up(report, "home") && feed.get(i + 1).is_some_and(|x| step_up(x, "away")) {
+            let away = &feed[i + 1];
+            assert_eq!(
+                report["event"]["StepUp"]["player"],
+                report["home"]["player"]
+            );
+            assert_eq!(away["event"]["StepUp"]["player"], away["away"]["player"]);
+            assert_ne!(report["home"]["player"], feed[i - 1]["home"]["player"]);
+            assert_ne!(away["away"]["player"], feed[i - 1]["away"]["player"]);
+            rotations.push(points);
+            points = 0;
+        }
+    }
+    assert!(!rotations.is_empty(), "{rotation} never rotated");
+    (rotations, serve_changes)
+}
+
+#[test]
+fn point_rotation_sends_new_players_every_point() {
+    let (rotations, _) = points_between_rotations("point", "short");
+    assert!(rotations.iter().all(|&x| x == 1), "{rotations:?}");
+}
+
+#[test]
+fn rallies_rotation_waits_for_enough_points() {
+    let (rotations, _) = points_between_rotations("rallies:2", "short");
+    assert!(rotations.iter().all(|&x| x == 2), "{rotations:?}");
+}
+
+#[test]
+fn serve_rotation_follows_the_serve() {
+    let (rotations, serve_changes) = points_between_rotations("serve", "best-of-1");
+    assert_eq!(rotations.len(), serve_changes);
+    // A game takes at least four points
+    assert!(rotations[0] >= 4, "{rotations:?}");
+}
+
+#[test]
+fn doubles_have_no_rotation() {
+    let output = Command::new(env!("CARGO_BIN_EXE_blaseball"))
+        .args(["play", "--kind", "doubles", "--rotation", "serve"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+
+    let error = String::from_utf8(output.stderr).unwrap();
+    assert!(error.contains("--rotation doesn't work with doubles games"));
+}
+
+#[test]
+fn doubles_servers_can_hit_aces() {
+    let output = Command::new(env!("CARGO_BIN_EXE_blaseball"))
+        .args(["play", "--kind", "doubles", "--seed", "1", "--no-delay"])
+        .args(["--scoring", "best-of-3", "--format", "jsonl"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let events: Vec<serde_json::Value> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|x| serde_json::from_str::<serde_json::Value>(x).unwrap()["event"].clone())
+        .collect();
+    let aces: Vec<_> = (0..events.len())
+        .filter(|&i| events[i]["Ace"].is_object())
+        .collect();
+    assert!(!aces.is_empty());
+
+    // Nobody gets to hit an ace back, the server wins the point straight away
+    for i in aces {
+        let score = events[i..].iter().find(|x| !x["Score"].is_null()).unwrap();
+        assert_eq!(score["Score"]["player"], events[i]["Ace"]["player"]);
+        assert!(events[i..]
+            .iter()
+            .take_while(|x| x["Score"].is_null())
+            .all(|x| x["Hit"].is_null() && x["Miss"].is_null()));
+    }
+}
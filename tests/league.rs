@@ -0,0 +1,218 @@
+use std::{collections::BTreeSet, path::Path, process::Command};
+
+/// Plays a match in the league, returning every report in the feed
+fn play(league: &Path, seed: &str, weathers: &Path) -> Vec<serde_json::Value> {
+    let output = Command::new(env!("CARGO_BIN_EXE_blaseball"))
+        .args([
+            "--seed",
+            seed,
+            "--no-delay",
+            "--format",
+            "jsonl",
+            "--league",
+        ])
+        .arg(league)
+        .arg("--weathers")
+        .arg(weathers)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|x| serde_json::from_str(x).unwrap())
+        .collect()
+}
+
+/// Writes a weather file with a single weather, which can replace a player when the match starts
+fn write_weathers(name: &str, effects: &str) -> std::path::PathBuf {
+    let weathers = std::env::temp_dir().join(name);
+    std::fs::write(
+        &weathers,
+        format!(
+            r#"
+[[weather]]
+name = "Still"
+label = "Still"
+announcement = "Nothing moves."
+
+[[weather.triggers]]
+phase = "match-start"
+chance = 1
+effects = [{effects}]
+"#
+        ),
+    )
+    .unwrap();
+    weathers
+}
+
+fn ids(feed: &[serde_json::Value], key: &str) -> BTreeSet<String> {
+    feed.iter()
+        .flat_map(|x| [&x["home"][key], &x["away"][key]])
+        .map(|x| x.as_str().unwrap().to_owned())
+        .collect()
+}
+
+/// Whether the save has the ID in it. JSON saves write it out as text, and binary saves as its
+/// 16 raw bytes
+fn save_has(save: &[u8], id: &str) -> bool {
+    if save.starts_with(b"{") {
+        return String::from_utf8_lossy(save).contains(id);
+    }
+    let hex = id.replace('-', "");
+    let bytes: Vec<u8> = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect();
+    save.windows(bytes.len()).any(|x| x == bytes)
+}
+
+fn league_persists(file_name: &str) {
+    let league = std::env::temp_dir().join(file_name);
+    let _ = std::fs::remove_file(&league);
+    let creating = write_weathers(&format!("{file_name}-creating.toml"), r#""replace-player""#);
+    let still = write_weathers(&format!("{file_name}-still.toml"), "");
+
+    let first = play(&league, "1", &creating);
+    let created = first
+        .iter()
+        .find_map(|x| x["event"]["Replacement"]["player"].as_str())
+        .unwrap()
+        .to_owned();
+
+    // The second match plays with the saved teams instead of making new ones
+    let second = play(&league, "2", &still);
+    assert_eq!(ids(&first, "team"), ids(&second, "team"));
+
+    let save = std::fs::read(&league).unwrap();
+    for id in ids(&first, "team")
+        .iter()
+        .chain(&ids(&first, "player"))
+        .chain([&created])
+    {
+        assert!(save_has(&save, id), "{id} is missing from the save");
+    }
+
+    std::fs::remove_file(&league).unwrap();
+    std::fs::remove_file(&creating).unwrap();
+    std::fs::remove_file(&still).unwrap();
+}
+
+#[test]
+fn json_league_persists() {
+    league_persists("blaseball-test-league.json");
+}
+
+#[test]
+fn binary_league_persists() {
+    league_persists("blaseball-test-league.bin");
+}
+
+/// Runs the game on the league, returning the exit code and whatever it printed as an error
+fn run_failing(league: &Path) -> (Option<i32>, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_blaseball"))
+        .args(["--seed", "1", "--no-delay", "--league"])
+        .arg(league)
+        .env("RUST_BACKTRACE", "1")
+        .output()
+        .unwrap();
+    (
+        output.status.code(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn old_json_leagues_load() {
+    let league = std::env::temp_dir().join("blaseball-test-old-league.json");
+    let team = |id: &str, name: &str, players: [&str; 2]| {
+        format!(
+            r#""{id}": {{"name": "{name}", "players": ["{}", "{}"], "current_player": 0}}"#,
+            players[0], players[1]
+        )
+    };
+    let player = |id: &str, name: &str| {
+        format!(
+            r#""{id}": {{"name": "{name}", "control": 0.5, "speed": 0.5, "distractability": 0.5}}"#
+        )
+    };
+    let players = [
+        "00000000-0000-0000-0000-000000000001",
+        "00000000-0000-0000-0000-000000000002",
+        "00000000-0000-0000-0000-000000000003",
+        "00000000-0000-0000-0000-000000000004",
+    ];
+    // A league the way the first version saved it, with only teams and players
+    std::fs::write(
+        &league,
+        format!(
+            r#"{{"version": 1, "data": {{"teams": {{{}, {}}}, "players": {{{}}}}}}}"#,
+            team(
+                "10000000-0000-0000-0000-000000000001",
+                "The Olds",
+                [players[0], players[1]]
+            ),
+            team(
+                "10000000-0000-0000-0000-000000000002",
+                "The Elders",
+                [players[2], players[3]]
+            ),
+            players
+                .iter()
+                .enumerate()
+                .map(|(i, id)| player(id, &format!("Player {i}")))
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_blaseball"))
+        .args([
+            "--seed",
+            "1",
+            "--no-delay",
+            "--home",
+            "The Olds",
+            "--league",
+        ])
+        .arg(&league)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("The Olds"));
+
+    // It's saved again in the current layout, with the new attributes filled in
+    let save: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&league).unwrap()).unwrap();
+    assert!(save["version"].as_u64().unwrap() > 1);
+    assert!(save["data"]["players"][players[0]]["stamina"].is_f64());
+
+    std::fs::remove_file(&league).unwrap();
+}
+
+#[test]
+fn broken_saves_are_explained() {
+    let league = std::env::temp_dir().join("blaseball-test-broken-league.bin");
+    std::fs::write(&league, b"not a league").unwrap();
+    let (code, error) = run_failing(&league);
+    assert_eq!(code, Some(1));
+    assert!(error.contains("not a save file"), "{error}");
+    assert!(!error.contains("panicked"), "{error}");
+    std::fs::remove_file(&league).unwrap();
+
+    let league = std::env::temp_dir().join("blaseball-test-future-league.json");
+    std::fs::write(&league, r#"{"version": 99, "data": {}}"#).unwrap();
+    let (code, error) = run_failing(&league);
+    assert_eq!(code, Some(1));
+    assert!(
+        error.contains("save format version 99 is not supported"),
+        "{error}"
+    );
+    assert!(!error.contains("panicked"), "{error}");
+    std::fs::remove_file(&league).unwrap();
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use std::process::Command;
+
+use common::run;
+
+fn playoffs(file_name: &str, bracket: &str) -> String {
+    let league = std::env::temp_dir().join(file_name);
+    let _ = std::fs::remove_file(&league);
+
+    run(&league, &["season", "--seed", "4", "--teams", "6"]);
+    let output = run(
+        &league,
+        &["playoffs", "--seed", "2", "--bracket", bracket, "--series", "3"],
+    );
+
+    std::fs::remove_file(&league).unwrap();
+    output
+}
+
+#[test]
+fn single_elimination_crowns_a_champion() {
+    let output = playoffs("blaseball-test-single.json", "single");
+
+    assert!(output.contains("Round 1"));
+    assert_eq!(output.matches("Champions: ").count(), 1);
+    // Every team but the champion gets knocked out exactly once
+    assert_eq!(output.lines().last().unwrap().matches(", ").count(), 4);
+}
+
+#[test]
+fn double_elimination_has_a_losers_bracket() {
+    let output = playoffs("blaseball-test-double.json", "double");
+
+    assert!(output.contains("Losers bracket: "));
+    assert_eq!(output.matches("Champions: ").count(), 1);
+}
+
+#[test]
+fn playoffs_need_a_season() {
+    let league = std::env::temp_dir().join("blaseball-test-no-season.json");
+    let _ = std::fs::remove_file(&league);
+
+    let output = run(&league, &["playoffs"]);
+    assert!(output.contains("There's no season"));
+}
+
+#[test]
+fn playoffs_need_teams() {
+    let league = std::env::temp_dir().join("blaseball-test-no-teams.json");
+    let _ = std::fs::remove_file(&league);
+
+    run(&league, &["season", "--seed", "4", "--teams", "4"]);
+    let output = Command::new(env!("CARGO_BIN_EXE_blaseball"))
+        .args(["playoffs", "--teams", "0", "--league"])
+        .arg(&league)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("--teams expects at least one team"));
+    std::fs::remove_file(&league).unwrap();
+
+    // A season without any teams leaves nobody to seed
+    run(&league, &["season", "--seed", "4", "--teams", "0"]);
+    let output = run(&league, &["playoffs"]);
+    assert!(output.contains("There are no teams in the standings"));
+    std::fs::remove_file(&league).unwrap();
+}
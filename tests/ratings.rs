@@ -0,0 +1,37 @@
+mod common;
+
+use common::run;
+
+#[test]
+fn ratings_follow_every_match() {
+    let league = std::env::temp_dir().join("blaseball-test-ratings.json");
+    let _ = std::fs::remove_file(&league);
+
+    assert_eq!(
+        run(&league, &["ratings"]),
+        "Nobody has played a rated match yet.\n"
+    );
+
+    run(&league, &["season", "--seed", "3", "--teams", "4"]);
+    let output = run(&league, &["ratings"]);
+    std::fs::remove_file(&league).unwrap();
+
+    assert!(output.starts_with("Ratings after 6 matches"));
+
+    // Whatever one team gains, the team it played loses
+    let team_ratings: Vec<f64> = output
+        .lines()
+        .skip(3)
+        .take(4)
+        .map(|line| {
+            line.split_whitespace()
+                .rev()
+                .nth(1)
+                .unwrap()
+                .parse()
+                .unwrap()
+        })
+        .collect();
+    let average = team_ratings.iter().sum::<f64>() / 4.0;
+    assert!((average - 1500.0).abs() < 1.0);
+}
@@ -0,0 +1,119 @@
+mod common;
+
+use std::{path::Path, process::Command};
+
+use common::run;
+
+fn season(league: &Path, extra: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_blaseball"))
+        .args(["season", "--seed", "4", "--league"])
+        .arg(league)
+        .args(extra)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn season_continues_across_runs() {
+    let league = std::env::temp_dir().join("blaseball-test-season.json");
+    let _ = std::fs::remove_file(&league);
+
+    let first = season(&league, &["--teams", "5", "--days", "2"]);
+    assert!(first.contains("A new season begins with 5 teams over 5 days!"));
+    assert!(first.contains("Day 2 of 5"));
+    assert!(!first.contains("Day 3 of 5"));
+
+    let second = season(&league, &[]);
+    assert!(!second.contains("A new season begins"));
+    assert!(second.contains("Day 3 of 5"));
+    assert!(second.contains("Day 5 of 5"));
+    assert!(second.contains("The season is over!"));
+
+    std::fs::remove_file(&league).unwrap();
+}
+
+#[test]
+fn injured_players_sit_out_across_runs() {
+    let league = std::env::temp_dir().join("blaseball-test-injuries.json");
+    let _ = std::fs::remove_file(&league);
+
+    let load = || -> serde_json::Value {
+        serde_json::from_slice(&std::fs::read(&league).unwrap()).unwrap()
+    };
+
+    let first = run(
+        &league,
+        &["season", "--seed", "6", "--teams", "8", "--days", "3"],
+    );
+    let injured = first
+        .lines()
+        .find_map(|x| x.strip_prefix("Injured: "))
+        .unwrap();
+    let (name, days) = injured.split_once(" (").unwrap();
+    let days: usize = days.split_once(' ').unwrap().0.parse().unwrap();
+
+    // The injury is in the save, and keeps counting down in the next run
+    let mut save = load();
+    let (id, _) = save["data"]["players"]
+        .as_object()
+        .unwrap()
+        .iter()
+        .find(|(_, x)| x["name"] == name)
+        .unwrap();
+    let id = id.clone();
+    assert_eq!(
+        save["data"]["players"][&id]["condition"]["injured_days"],
+        days
+    );
+    let second = run(&league, &["season", "--days", "1"]);
+    assert!(second.contains(&format!("{name} ({} day", days - 1)));
+
+    // Even if the injured player is up next, a healthy one takes their place
+    save = load();
+    let (team, lineup) = save["data"]["teams"]
+        .as_object_mut()
+        .unwrap()
+        .iter_mut()
+        .find(|(_, x)| {
+            x["players"]
+                .as_array()
+                .unwrap()
+                .contains(&id.clone().into())
+        })
+        .unwrap();
+    let index = lineup["players"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .position(|x| *x == id.as_str())
+        .unwrap();
+    lineup["current_player"] = index.into();
+    let team_name = lineup["name"].as_str().unwrap().to_owned();
+    let team = team.clone();
+    std::fs::write(&league, serde_json::to_vec(&save).unwrap()).unwrap();
+
+    let feed = run(
+        &league,
+        &[
+            "--seed",
+            "1",
+            "--no-delay",
+            "--format",
+            "jsonl",
+            "--home",
+            &team_name,
+        ],
+    );
+    let reports: Vec<serde_json::Value> = feed
+        .lines()
+        .map(|x| serde_json::from_str(x).unwrap())
+        .collect();
+    assert_eq!(reports[0]["home"]["team"], team.as_str());
+    assert!(reports[0]["event"]["StepUp"].is_object());
+    assert!(reports.iter().all(|x| x["home"]["player"] != id.as_str()));
+
+    std::fs::remove_file(&league).unwrap();
+}
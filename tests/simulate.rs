@@ -0,0 +1,85 @@
+use std::process::Command;
+
+fn simulate(seed: &str) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_blaseball"))
+        .args(["simulate", "--seed", seed, "--matches", "50"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn simulation_is_reproducible() {
+    assert_eq!(simulate("8"), simulate("8"));
+}
+
+/// The number on the line that starts with `label`, without any unit after it
+fn stat(stats: &str, label: &str) -> f64 {
+    let line = stats.lines().find(|x| x.starts_with(label)).unwrap();
+    line[label.len()..]
+        .trim()
+        .trim_end_matches('%')
+        .split_whitespace()
+        .next()
+        .unwrap()
+        .parse()
+        .unwrap()
+}
+
+#[test]
+fn simulation_reports_statistics() {
+    let stats = simulate("8");
+
+    assert!(stats.contains("50 matches"));
+    let home = stat(&stats, "The Speedles win");
+    let away = stat(&stats, "The Spabbles win");
+    assert!((home + away - 100.0).abs() < 0.01, "{home}% and {away}%");
+
+    // Somebody has to get to five points before a match is over
+    assert!(stat(&stats, "Points per match:") >= 5.0);
+    assert!(stat(&stats, "Rally length:") > 0.0);
+    assert!(stat(&stats, "Longest rally:") >= stat(&stats, "Rally length:"));
+    assert!(stat(&stats, "Aces per match:") >= 0.0);
+    assert!(stat(&stats, "Double faults per match:") >= 0.0);
+
+    // Every match ends with one of the final scores
+    let final_scores: usize = stats
+        .split_once("Final scores:\n")
+        .unwrap()
+        .1
+        .lines()
+        .take_while(|x| x.starts_with("  "))
+        .map(|x| {
+            x.split_whitespace()
+                .nth(1)
+                .unwrap()
+                .parse::<usize>()
+                .unwrap()
+        })
+        .sum();
+    assert_eq!(final_scores, 50);
+    assert!(stats.contains("Weather effects:"));
+}
+
+#[test]
+fn simulation_does_not_depend_on_thread_count() {
+    let simulate = |threads: &str| {
+        Command::new(env!("CARGO_BIN_EXE_blaseball"))
+            .args([
+                "simulate",
+                "--seed",
+                "3",
+                "--matches",
+                "200",
+                "--threads",
+                threads,
+            ])
+            .output()
+            .unwrap()
+            .stdout
+    };
+
+    assert_eq!(simulate("1"), simulate("4"));
+}
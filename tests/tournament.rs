@@ -0,0 +1,73 @@
+mod common;
+
+use common::run;
+
+fn tournament(file_name: &str, args: &[&str]) -> String {
+    let league = std::env::temp_dir().join(file_name);
+    let _ = std::fs::remove_file(&league);
+
+    let output = run(&league, args);
+
+    std::fs::remove_file(&league).unwrap();
+    output
+}
+
+/// Every pair of teams that played each other in the tournament, once per game
+fn matchups(output: &str) -> Vec<(String, String)> {
+    let mut matchups: Vec<_> = output
+        .lines()
+        .filter_map(|line| {
+            let (home, away) = line.trim().split_once(" - ")?;
+            let (home, _) = home.rsplit_once(' ')?;
+            let (_, away) = away.split_once(' ')?;
+            Some(if home < away {
+                (home.to_owned(), away.to_owned())
+            } else {
+                (away.to_owned(), home.to_owned())
+            })
+        })
+        .collect();
+    matchups.sort();
+    matchups
+}
+
+#[test]
+fn swiss_avoids_rematches_and_hands_out_byes() {
+    let output = tournament(
+        "blaseball-test-swiss.json",
+        &["tournament", "--seed", "5", "--teams", "7", "--rounds", "4"],
+    );
+
+    assert!(output.contains("A tournament begins with 7 teams over 4 rounds!"));
+    assert_eq!(output.matches("get a bye.").count(), 4);
+    assert!(output.contains("Final rankings"));
+
+    let mut unique = matchups(&output);
+    assert_eq!(unique.len(), 12);
+    unique.dedup();
+    assert_eq!(unique.len(), 12);
+}
+
+#[test]
+fn round_robin_plays_every_pairing_once() {
+    let output = tournament(
+        "blaseball-test-round-robin.json",
+        &[
+            "tournament",
+            "--seed",
+            "5",
+            "--teams",
+            "5",
+            "--system",
+            "round-robin",
+        ],
+    );
+
+    assert!(output.contains("A tournament begins with 5 teams over 5 rounds!"));
+    assert_eq!(output.matches("sit this round out.").count(), 5);
+
+    let mut unique = matchups(&output);
+    assert_eq!(unique.len(), 10);
+    unique.dedup();
+    assert_eq!(unique.len(), 10);
+}
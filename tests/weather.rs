@@ -0,0 +1,308 @@
+use std::{path::Path, process::Command};
+
+fn play(weathers: &Path) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_blaseball"))
+        .args(["--seed", "5", "--no-delay", "--weathers"])
+        .arg(weathers)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn leagues_can_bring_their_own_weather() {
+    let weathers = std::env::temp_dir().join("blaseball-test-sleet.toml");
+    std::fs::write(
+        &weathers,
+        r#"
+[[weather]]
+name = "Sleet"
+label = "Sleet"
+announcement = "It's coming down sideways."
+
+[[weather.triggers]]
+phase = "pre-hit"
+chance = 1
+effects = ["watch", "prevent-hit"]
+"#,
+    )
+    .unwrap();
+
+    let output = play(&weathers);
+    std::fs::remove_file(&weathers).unwrap();
+    assert!(output.status.success());
+
+    // Nobody ever gets to hit the ball, they get watched every time
+    let feed = String::from_utf8(output.stdout).unwrap();
+    assert!(feed.contains("---------------------[Sleet]"));
+    assert!(!feed.contains(" hits!"));
+    assert_eq!(
+        feed.matches(" is being watched.").count(),
+        feed.matches("doesn't manage to hit!").count()
+    );
+    assert!(feed.contains("Weather: Watch x"));
+}
+
+/// The names of every event in a match of the kind played with the weathers, in order
+fn events(kind: &str, weathers: &Path) -> Vec<String> {
+    let output = Command::new(env!("CARGO_BIN_EXE_blaseball"))
+        .args([
+            "play",
+            "--kind",
+            kind,
+            "--seed",
+            "5",
+            "--no-delay",
+            "--format",
+            "jsonl",
+            "--weathers",
+        ])
+        .arg(weathers)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let report: serde_json::Value = serde_json::from_str(line).unwrap();
+            match &report["event"] {
+                serde_json::Value::String(name) => name.clone(),
+                event => event.as_object().unwrap().keys().next().unwrap().clone(),
+            }
+        })
+        .collect()
+}
+
+fn count(events: &[String], names: &[&str]) -> usize {
+    events
+        .iter()
+        .filter(|x| names.contains(&x.as_str()))
+        .count()
+}
+
+/// Checks that every `event` comes right after one of `after`
+fn comes_after(events: &[String], event: &str, after: &[&str]) {
+    for i in (1..events.len()).filter(|&i| events[i] == event) {
+        let before = events[i - 1].as_str();
+        assert!(after.contains(&before), "{event} came after {before}");
+    }
+}
+
+fn weather_acts_at_every_phase(kind: &str) {
+    let weathers = std::env::temp_dir().join(format!("blaseball-test-{kind}-stands.toml"));
+    std::fs::write(
+        &weathers,
+        r#"
+[[weather]]
+name = "Stands"
+label = "Stands"
+announcement = "The crowd is restless."
+
+[[weather.triggers]]
+phase = "match-start"
+chance = 1
+effects = ["swap"]
+
+[[weather.triggers]]
+phase = "serve"
+chance = 1
+effects = ["shuffle"]
+
+[[weather.triggers]]
+phase = "pre-hit"
+chance = 1
+effects = ["watch"]
+
+[[weather.triggers]]
+phase = "post-hit"
+chance = 1
+effects = ["recruit"]
+
+[[weather.triggers]]
+phase = "score"
+chance = 1
+effects = ["replace-player"]
+
+[[weather.triggers]]
+phase = "match-end"
+chance = 1
+effects = ["change-weather"]
+"#,
+    )
+    .unwrap();
+
+    let events = events(kind, &weathers);
+    std::fs::remove_file(&weathers).unwrap();
+
+    let hits = ["Hit", "PowerShot", "Lob", "DropShot"];
+    let swings = ["Hit", "PowerShot", "Lob", "DropShot", "Miss"];
+    // The players trade places before anybody serves
+    assert_eq!(events[0], "Swap");
+    assert_eq!(count(&events, &["Swap"]), 1);
+    // Aces are called before the serve is over
+    comes_after(&events, "Shuffle", &["Serve", "Ace"]);
+    assert_eq!(count(&events, &["Shuffle"]), count(&events, &["Serve"]));
+    // Players get watched right before every swing, whether it connects or not
+    for i in (0..events.len()).filter(|&i| events[i] == "Watch") {
+        assert!(swings.contains(&events[i + 1].as_str()));
+    }
+    assert_eq!(count(&events, &["Watch"]), count(&events, &swings));
+    comes_after(&events, "Recruit", &hits);
+    assert_eq!(count(&events, &["Recruit"]), count(&events, &hits));
+    comes_after(&events, "Replaced", &["Score"]);
+    assert_eq!(count(&events, &["Replaced"]), count(&events, &["Score"]));
+    assert_eq!(events[events.len() - 2..], ["Win", "WeatherChange"]);
+}
+
+#[test]
+fn weather_acts_at_every_phase_of_singles() {
+    weather_acts_at_every_phase("singles");
+}
+
+#[test]
+fn weather_acts_at_every_phase_of_doubles() {
+    weather_acts_at_every_phase("doubles");
+}
+
+#[test]
+fn weathers_cant_keep_picking_themselves() {
+    for effect in ["mimic", "change-weather"] {
+        let weathers = std::env::temp_dir().join(format!("blaseball-test-{effect}-loop.toml"));
+        std::fs::write(
+            &weathers,
+            format!(
+                r#"
+[[weather]]
+name = "Loop"
+label = "Loop"
+announcement = "It's the same as it ever was."
+
+[[weather.triggers]]
+phase = "pre-hit"
+chance = 1
+effects = ["{effect}"]
+"#
+            ),
+        )
+        .unwrap();
+
+        let output = play(&weathers);
+        std::fs::remove_file(&weathers).unwrap();
+        assert!(output.status.success(), "{effect} never stops");
+    }
+}
+
+#[test]
+fn only_pre_hit_triggers_can_prevent_hits() {
+    let weathers = std::env::temp_dir().join("blaseball-test-early-fog.toml");
+    std::fs::write(
+        &weathers,
+        r#"
+[[weather]]
+name = "Fog"
+label = "Fog"
+announcement = "Nobody can see a thing."
+
+[[weather.triggers]]
+phase = "serve"
+chance = 0.5
+effects = ["prevent-hit"]
+"#,
+    )
+    .unwrap();
+
+    let output = play(&weathers);
+    std::fs::remove_file(&weathers).unwrap();
+    assert!(!output.status.success());
+
+    let error = String::from_utf8(output.stderr).unwrap();
+    assert!(error.contains("weather Fog can only prevent hits in the pre-hit phase"));
+}
+
+#[test]
+fn broken_weathers_are_rejected() {
+    let weathers = std::env::temp_dir().join("blaseball-test-broken-weather.toml");
+    std::fs::write(
+        &weathers,
+        r#"
+[[weather]]
+name = "Hail"
+label = "Hail"
+announcement = "Ouch."
+
+[[weather.triggers]]
+phase = "pre-hit"
+chance = 2
+effects = ["shuffle"]
+"#,
+    )
+    .unwrap();
+
+    let output = play(&weathers);
+    std::fs::remove_file(&weathers).unwrap();
+    assert!(!output.status.success());
+
+    let error = String::from_utf8(output.stderr).unwrap();
+    assert!(error.contains("weather Hail has a trigger with a chance of 2"));
+}
+
+#[test]
+fn weights_have_to_be_finite() {
+    // How each weight is written in the file and how it shows up in the error
+    for (weight, shown) in [("nan", "NaN"), ("inf", "inf")] {
+        let weathers = std::env::temp_dir().join(format!("blaseball-test-{weight}-weight.toml"));
+        std::fs::write(
+            &weathers,
+            format!(
+                r#"
+[[weather]]
+name = "Haze"
+label = "Haze"
+announcement = "It's hard to tell how likely this is."
+weight = {weight}
+"#
+            ),
+        )
+        .unwrap();
+
+        let output = play(&weathers);
+        std::fs::remove_file(&weathers).unwrap();
+        assert!(!output.status.success());
+
+        let error = String::from_utf8(output.stderr).unwrap();
+        assert!(error.contains(&format!("weather Haze has a weight of {shown}")));
+        assert!(!error.contains("panicked"));
+    }
+}
+
+#[test]
+fn weather_can_keep_doubles_from_hitting() {
+    let weathers = std::env::temp_dir().join("blaseball-test-doubles-fog.toml");
+    std::fs::write(
+        &weathers,
+        r#"
+[[weather]]
+name = "Fog"
+label = "Fog"
+announcement = "Nobody can see a thing."
+
+[[weather.triggers]]
+phase = "pre-hit"
+chance = 1
+effects = ["prevent-hit"]
+"#,
+    )
+    .unwrap();
+
+    let events = events("doubles", &weathers);
+    std::fs::remove_file(&weathers).unwrap();
+
+    // Every serve that goes in is never returned, if it isn't an ace already
+    assert!(count(&events, &["Prevented"]) > 0);
+    assert_eq!(
+        count(&events, &["Prevented", "Ace"]),
+        count(&events, &["Serve"])
+    );
+    assert_eq!(count(&events, &["Hit", "PowerShot", "Lob", "DropShot"]), 0);
+}